@@ -0,0 +1,115 @@
+//! Combiner benchmarks
+//!
+//! Compares the combiners, which stream their input through an incremental KDF, with the
+//! previous approach of chaining byte iterators and collecting them before hashing.
+
+use concrete_hybrid_kem::hybrid::{c2pri_combiner, universal_combiner};
+use concrete_hybrid_kem::kdf::Sha3_256;
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use sha3::Digest;
+use std::hint::black_box;
+
+/// Component sizes of ML-KEM-768 + P-256 and ML-KEM-1024 + P-384
+const INSTANCES: [(&str, usize, usize, usize, usize); 2] = [
+    ("MLKEM768-P256", 1184, 1088, 65, 32),
+    ("MLKEM1024-P384", 1568, 1568, 97, 48),
+];
+
+const LABEL: &[u8] = b"label";
+
+/// The universal combiner as it was computed before `Kdf` became incremental
+fn universal_combiner_collect(
+    ss_pq: &[u8],
+    ss_t: &[u8],
+    ct_pq: &[u8],
+    ct_t: &[u8],
+    ek_pq: &[u8],
+    ek_t: &[u8],
+    label: &[u8],
+) -> Vec<u8> {
+    let input: Vec<u8> = ss_pq
+        .iter()
+        .chain(ss_t.iter())
+        .chain(ct_pq.iter())
+        .chain(ct_t.iter())
+        .chain(ek_pq.iter())
+        .chain(ek_t.iter())
+        .chain(label.iter())
+        .cloned()
+        .collect();
+    sha3::Sha3_256::digest(&input).to_vec()
+}
+
+fn combiners(c: &mut Criterion) {
+    let mut group = c.benchmark_group("universal_combiner");
+    for (name, ek_pq_size, ct_pq_size, element_size, ss_t_size) in INSTANCES {
+        let ss_pq = vec![1u8; 32];
+        let ss_t = vec![2u8; ss_t_size];
+        let ct_pq = vec![3u8; ct_pq_size];
+        let ct_t = vec![4u8; element_size];
+        let ek_pq = vec![5u8; ek_pq_size];
+        let ek_t = vec![6u8; element_size];
+
+        assert_eq!(
+            universal_combiner::<Sha3_256>(
+                &ss_pq, &ss_t, &ct_pq, &ct_t, &ek_pq, &ek_t, LABEL, None
+            ),
+            universal_combiner_collect(&ss_pq, &ss_t, &ct_pq, &ct_t, &ek_pq, &ek_t, LABEL)
+        );
+
+        group.bench_function(BenchmarkId::new("incremental", name), |b| {
+            b.iter(|| {
+                universal_combiner::<Sha3_256>(
+                    black_box(&ss_pq),
+                    black_box(&ss_t),
+                    black_box(&ct_pq),
+                    black_box(&ct_t),
+                    black_box(&ek_pq),
+                    black_box(&ek_t),
+                    LABEL,
+                    None,
+                )
+            })
+        });
+
+        group.bench_function(BenchmarkId::new("iterator_collect", name), |b| {
+            b.iter(|| {
+                universal_combiner_collect(
+                    black_box(&ss_pq),
+                    black_box(&ss_t),
+                    black_box(&ct_pq),
+                    black_box(&ct_t),
+                    black_box(&ek_pq),
+                    black_box(&ek_t),
+                    LABEL,
+                )
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("c2pri_combiner");
+    for (name, _, _, element_size, ss_t_size) in INSTANCES {
+        let ss_pq = vec![1u8; 32];
+        let ss_t = vec![2u8; ss_t_size];
+        let ct_t = vec![4u8; element_size];
+        let ek_t = vec![6u8; element_size];
+
+        group.bench_function(BenchmarkId::new("incremental", name), |b| {
+            b.iter(|| {
+                c2pri_combiner::<Sha3_256>(
+                    black_box(&ss_pq),
+                    black_box(&ss_t),
+                    black_box(&ct_t),
+                    black_box(&ek_t),
+                    LABEL,
+                    None,
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, combiners);
+criterion_main!(benches);
@@ -0,0 +1,101 @@
+//! Benchmarks of the components the hybrid KEMs are built from
+//!
+//! `random_scalar` is the SHAKE-based scalar sampling, including the rejection loop for the NIST
+//! curves, and `exp` is a single scalar multiplication of an arbitrary element.
+
+use concrete_hybrid_kem::group::{NominalGroup, P256, P384, X25519};
+use concrete_hybrid_kem::kdf::{Kdf, Sha3_256};
+use concrete_hybrid_kem::kem::{EncapsDerand, MlKem512, MlKem768, MlKem1024};
+use concrete_hybrid_kem::prg::{Prg, Shake256};
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+
+fn bench_group<G: NominalGroup>(c: &mut Criterion, name: &str) {
+    let seed = vec![1u8; G::SEED_SIZE];
+    let scalar = G::random_scalar(&seed);
+    let element = G::exp(&G::generator(), &G::random_scalar(&vec![2u8; G::SEED_SIZE]));
+
+    let mut group = c.benchmark_group(name);
+    group.bench_function("random_scalar", |b| {
+        b.iter(|| G::random_scalar(black_box(&seed)))
+    });
+    group.bench_function("exp", |b| {
+        b.iter(|| G::exp(black_box(&element), black_box(&scalar)))
+    });
+    group.bench_function("element_to_shared_secret", |b| {
+        b.iter(|| G::element_to_shared_secret(black_box(&element)))
+    });
+    group.finish();
+}
+
+fn bench_kem<K: EncapsDerand>(c: &mut Criterion, name: &str) {
+    let seed = vec![1u8; K::SEED_SIZE];
+    let (dk, ek, _) = K::derive_key_pair(&seed);
+    let (ct, _) = K::encaps_derand(&ek, &vec![2u8; K::RANDOMNESS_SIZE]);
+
+    let mut group = c.benchmark_group(name);
+    group.bench_function("derive_key_pair", |b| {
+        b.iter(|| K::derive_key_pair(black_box(&seed)))
+    });
+    group.bench_function("decaps", |b| {
+        b.iter(|| K::decaps(black_box(&dk), black_box(&ct)))
+    });
+    group.finish();
+}
+
+fn groups(c: &mut Criterion) {
+    bench_group::<X25519>(c, "X25519");
+    bench_group::<P256>(c, "P-256");
+    bench_group::<P384>(c, "P-384");
+}
+
+fn kems(c: &mut Criterion) {
+    bench_kem::<MlKem512>(c, "ML-KEM-512");
+    bench_kem::<MlKem768>(c, "ML-KEM-768");
+    bench_kem::<MlKem1024>(c, "ML-KEM-1024");
+}
+
+/// SHAKE256 output lengths used to expand the seed of each instance: ML-KEM's 64-byte seed plus
+/// the group's seed
+fn prg(c: &mut Criterion) {
+    let seed = [1u8; 32];
+    let mut group = c.benchmark_group("SHAKE256");
+    for (name, size) in [
+        ("MLKEM768-X25519", 64 + 32),
+        ("MLKEM768-P256", 64 + 32),
+        ("MLKEM1024-P384", 64 + 48),
+    ] {
+        let mut output = vec![0u8; size];
+        group.bench_function(BenchmarkId::new("generate", name), |b| {
+            b.iter(|| Shake256::generate(black_box(&seed), black_box(&mut output)))
+        });
+    }
+    group.finish();
+}
+
+/// SHA3-256 over the input of the C2PRI combiner (ss_pq, ss_t, ct_t, ek_t, label) and of the
+/// universal combiner, which adds ct_pq and ek_pq
+fn kdf(c: &mut Criterion) {
+    let mut group = c.benchmark_group("SHA3-256");
+    for (name, size) in [
+        ("c2pri/MLKEM768-X25519", 32 + 32 + 32 + 32 + 6),
+        ("c2pri/MLKEM1024-P384", 32 + 48 + 97 + 97 + 6),
+        (
+            "universal/MLKEM768-X25519",
+            32 + 32 + 1088 + 32 + 1184 + 32 + 6,
+        ),
+        (
+            "universal/MLKEM1024-P384",
+            32 + 48 + 1568 + 97 + 1568 + 97 + 6,
+        ),
+    ] {
+        let input = vec![1u8; size];
+        group.bench_function(BenchmarkId::new("compute", name), |b| {
+            b.iter(|| Sha3_256::compute(&[black_box(&input)]))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, groups, kems, prg, kdf);
+criterion_main!(benches);
@@ -0,0 +1,60 @@
+//! Benchmarks of GU against GC on the same components
+//!
+//! The two frameworks differ only in the combiner: GU hashes `ct_pq` and `ek_pq` into the shared
+//! secret and GC does not.  Key derivation is the same for both.
+
+use concrete_hybrid_kem::hybrid::{GC, GU, HybridKem};
+use concrete_hybrid_kem::{
+    MlKem768P256Constants, MlKem768X25519Constants, MlKem1024P384Constants, group, kdf, kem, prg,
+};
+use criterion::{
+    BenchmarkGroup, Criterion, criterion_group, criterion_main, measurement::WallTime,
+};
+use std::hint::black_box;
+
+type Gu768P256 =
+    GU<kem::MlKem768, group::P256, prg::Shake256, kdf::Sha3_256, MlKem768P256Constants>;
+type Gc768P256 =
+    GC<kem::MlKem768, group::P256, prg::Shake256, kdf::Sha3_256, MlKem768P256Constants>;
+type Gu768X25519 =
+    GU<kem::MlKem768, group::X25519, prg::Shake256, kdf::Sha3_256, MlKem768X25519Constants>;
+type Gc768X25519 =
+    GC<kem::MlKem768, group::X25519, prg::Shake256, kdf::Sha3_256, MlKem768X25519Constants>;
+type Gu1024P384 =
+    GU<kem::MlKem1024, group::P384, prg::Shake256, kdf::Sha3_256, MlKem1024P384Constants>;
+type Gc1024P384 =
+    GC<kem::MlKem1024, group::P384, prg::Shake256, kdf::Sha3_256, MlKem1024P384Constants>;
+
+fn bench_framework<K: HybridKem>(group: &mut BenchmarkGroup<WallTime>, framework: &str) {
+    let seed = vec![1u8; K::SEED_SIZE];
+    let randomness = vec![2u8; K::RANDOMNESS_SIZE];
+    let (dk, ek, _) = K::derive_key_pair(&seed);
+    let (ct, _) = K::encaps_derand(&ek, &randomness);
+
+    group.bench_function(format!("{}/encaps_derand", framework), |b| {
+        b.iter(|| K::encaps_derand(black_box(&ek), black_box(&randomness)))
+    });
+    group.bench_function(format!("{}/decaps", framework), |b| {
+        b.iter(|| K::decaps(black_box(&dk), black_box(&ct)))
+    });
+}
+
+fn frameworks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("MLKEM768-P256");
+    bench_framework::<Gu768P256>(&mut group, "GU");
+    bench_framework::<Gc768P256>(&mut group, "GC");
+    group.finish();
+
+    let mut group = c.benchmark_group("MLKEM768-X25519");
+    bench_framework::<Gu768X25519>(&mut group, "GU");
+    bench_framework::<Gc768X25519>(&mut group, "GC");
+    group.finish();
+
+    let mut group = c.benchmark_group("MLKEM1024-P384");
+    bench_framework::<Gu1024P384>(&mut group, "GU");
+    bench_framework::<Gc1024P384>(&mut group, "GC");
+    group.finish();
+}
+
+criterion_group!(benches, frameworks);
+criterion_main!(benches);
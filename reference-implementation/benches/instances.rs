@@ -0,0 +1,41 @@
+//! Benchmarks of the hybrid KEM instances in `lib.rs`
+//!
+//! Each operation on a GC instance expands the decapsulation key seed (`derive_key_pair`) before
+//! decapsulating, so the `keygen` figures are also the fixed cost that `decaps` pays for storing
+//! only the seed.
+
+use concrete_hybrid_kem::hybrid::HybridKem;
+use concrete_hybrid_kem::{MlKem768P256, MlKem768X25519, MlKem1024P384};
+use criterion::{Criterion, criterion_group, criterion_main};
+use rand::{SeedableRng, rngs::StdRng};
+use std::hint::black_box;
+
+fn bench_instance<K: HybridKem>(c: &mut Criterion, name: &str) {
+    let seed = vec![1u8; K::SEED_SIZE];
+    let randomness = vec![2u8; K::RANDOMNESS_SIZE];
+    let (dk, ek, _) = K::derive_key_pair(&seed);
+    let (ct, _) = K::encaps_derand(&ek, &randomness);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let mut group = c.benchmark_group(name);
+    group.bench_function("keygen", |b| {
+        b.iter(|| K::derive_key_pair(black_box(&seed)))
+    });
+    group.bench_function("encaps", |b| b.iter(|| K::encaps(black_box(&ek), &mut rng)));
+    group.bench_function("encaps_derand", |b| {
+        b.iter(|| K::encaps_derand(black_box(&ek), black_box(&randomness)))
+    });
+    group.bench_function("decaps", |b| {
+        b.iter(|| K::decaps(black_box(&dk), black_box(&ct)))
+    });
+    group.finish();
+}
+
+fn instances(c: &mut Criterion) {
+    bench_instance::<MlKem768P256>(c, "MLKEM768-P256");
+    bench_instance::<MlKem768X25519>(c, "MLKEM768-X25519");
+    bench_instance::<MlKem1024P384>(c, "MLKEM1024-P384");
+}
+
+criterion_group!(benches, instances);
+criterion_main!(benches);
@@ -0,0 +1,115 @@
+/* Generated by cbindgen from ffi/src/lib.rs; do not edit. */
+
+#ifndef CONCRETE_HYBRID_KEM_H
+#define CONCRETE_HYBRID_KEM_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The result of a hybrid KEM operation
+typedef enum HybridKemStatus {
+  // The operation succeeded and the outputs have been written
+  HYBRID_KEM_STATUS_OK = 0,
+  // A pointer argument was null
+  HYBRID_KEM_STATUS_NULL_POINTER = 1,
+  // A buffer length does not match the size for the instance
+  HYBRID_KEM_STATUS_INVALID_LENGTH = 2,
+  // An input could not be processed, e.g., an invalid curve point or an unknown algorithm
+  HYBRID_KEM_STATUS_INVALID_INPUT = 3,
+} HybridKemStatus;
+
+// The hybrid KEM instances.  Functions take these as a `uint32_t`, since C callers can pass any
+// value for an enum.
+typedef enum HybridKemAlgorithm {
+  HYBRID_KEM_ALGORITHM_ML_KEM768_P256 = 0,
+  HYBRID_KEM_ALGORITHM_ML_KEM768_X25519 = 1,
+  HYBRID_KEM_ALGORITHM_ML_KEM1024_P384 = 2,
+} HybridKemAlgorithm;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// The size of the seed for `hybrid_kem_keygen_derand`, or 0 for an unknown algorithm
+size_t hybrid_kem_seed_size(uint32_t algorithm);
+
+// The size of an encapsulation key, or 0 for an unknown algorithm
+size_t hybrid_kem_encapsulation_key_size(uint32_t algorithm);
+
+// The size of a decapsulation key, or 0 for an unknown algorithm
+size_t hybrid_kem_decapsulation_key_size(uint32_t algorithm);
+
+// The size of a ciphertext, or 0 for an unknown algorithm
+size_t hybrid_kem_ciphertext_size(uint32_t algorithm);
+
+// The size of a shared secret, or 0 for an unknown algorithm
+size_t hybrid_kem_shared_secret_size(uint32_t algorithm);
+
+// The size of the randomness for `hybrid_kem_encaps_derand`, or 0 for an unknown algorithm
+size_t hybrid_kem_randomness_size(uint32_t algorithm);
+
+// Derive a key pair from a seed
+//
+// # Safety
+//
+// Each pointer must be null or valid for as many bytes as the length that follows it, and the
+// outputs must not overlap.
+enum HybridKemStatus hybrid_kem_keygen_derand(uint32_t algorithm,
+                                              const uint8_t *seed,
+                                              size_t seed_len,
+                                              uint8_t *dk,
+                                              size_t dk_len,
+                                              uint8_t *ek,
+                                              size_t ek_len);
+
+// Encapsulate to an encapsulation key, using the operating system's random number generator
+//
+// # Safety
+//
+// Each pointer must be null or valid for as many bytes as the length that follows it, and the
+// outputs must not overlap.
+enum HybridKemStatus hybrid_kem_encaps(uint32_t algorithm,
+                                       const uint8_t *ek,
+                                       size_t ek_len,
+                                       uint8_t *ct,
+                                       size_t ct_len,
+                                       uint8_t *ss,
+                                       size_t ss_len);
+
+// Encapsulate to an encapsulation key with the given randomness
+//
+// # Safety
+//
+// Each pointer must be null or valid for as many bytes as the length that follows it, and the
+// outputs must not overlap.
+enum HybridKemStatus hybrid_kem_encaps_derand(uint32_t algorithm,
+                                              const uint8_t *ek,
+                                              size_t ek_len,
+                                              const uint8_t *randomness,
+                                              size_t randomness_len,
+                                              uint8_t *ct,
+                                              size_t ct_len,
+                                              uint8_t *ss,
+                                              size_t ss_len);
+
+// Decapsulate a ciphertext
+//
+// # Safety
+//
+// Each pointer must be null or valid for as many bytes as the length that follows it.
+enum HybridKemStatus hybrid_kem_decaps(uint32_t algorithm,
+                                       const uint8_t *dk,
+                                       size_t dk_len,
+                                       const uint8_t *ct,
+                                       size_t ct_len,
+                                       uint8_t *ss,
+                                       size_t ss_len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CONCRETE_HYBRID_KEM_H */
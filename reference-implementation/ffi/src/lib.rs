@@ -0,0 +1,434 @@
+//! C bindings for the concrete hybrid KEMs
+//!
+//! The crate builds as a `cdylib` and a `staticlib`.  The C header
+//! `include/concrete_hybrid_kem.h` is generated with cbindgen and checked in; the `header` test
+//! fails if it is out of date.  Every function takes the instance as one of the
+//! `HybridKemAlgorithm` values and works on caller-provided buffers, whose lengths must match the
+//! sizes reported for the instance.  Failures, including unknown instances and invalid curve
+//! points, are reported as a `HybridKemStatus`, and outputs are only written on success.
+
+use concrete_hybrid_kem::algorithm::{self, Algorithm, DynHybridKem};
+use std::slice;
+
+/// The hybrid KEM instances.  Functions take these as a `uint32_t`, since C callers can pass any
+/// value for an enum.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HybridKemAlgorithm {
+    MlKem768P256 = 0,
+    MlKem768X25519 = 1,
+    MlKem1024P384 = 2,
+}
+
+impl HybridKemAlgorithm {
+    const ALL: [HybridKemAlgorithm; 3] = [
+        HybridKemAlgorithm::MlKem768P256,
+        HybridKemAlgorithm::MlKem768X25519,
+        HybridKemAlgorithm::MlKem1024P384,
+    ];
+
+    fn from_u32(value: u32) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|&algorithm| algorithm as u32 == value)
+    }
+}
+
+impl From<HybridKemAlgorithm> for Algorithm {
+    fn from(algorithm: HybridKemAlgorithm) -> Self {
+        match algorithm {
+            HybridKemAlgorithm::MlKem768P256 => Algorithm::MlKem768P256,
+            HybridKemAlgorithm::MlKem768X25519 => Algorithm::MlKem768X25519,
+            HybridKemAlgorithm::MlKem1024P384 => Algorithm::MlKem1024P384,
+        }
+    }
+}
+
+/// The result of a hybrid KEM operation
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HybridKemStatus {
+    /// The operation succeeded and the outputs have been written
+    Ok = 0,
+    /// A pointer argument was null
+    NullPointer = 1,
+    /// A buffer length does not match the size for the instance
+    InvalidLength = 2,
+    /// An input could not be processed, e.g., an invalid curve point or an unknown algorithm
+    InvalidInput = 3,
+}
+
+type Result<T> = std::result::Result<T, HybridKemStatus>;
+
+impl From<algorithm::Error> for HybridKemStatus {
+    fn from(err: algorithm::Error) -> Self {
+        match err {
+            algorithm::Error::UnknownAlgorithm(_) | algorithm::Error::InvalidInput { .. } => {
+                HybridKemStatus::InvalidInput
+            }
+            algorithm::Error::InvalidLength { .. } => HybridKemStatus::InvalidLength,
+        }
+    }
+}
+
+/// Run an operation and report its outcome
+fn status(f: impl FnOnce() -> Result<()>) -> HybridKemStatus {
+    match f() {
+        Ok(()) => HybridKemStatus::Ok,
+        Err(status) => status,
+    }
+}
+
+/// # Safety
+///
+/// `ptr` must be null or valid for reads of `len` bytes.
+unsafe fn input<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8]> {
+    if ptr.is_null() {
+        return Err(HybridKemStatus::NullPointer);
+    }
+    Ok(unsafe { slice::from_raw_parts(ptr, len) })
+}
+
+/// # Safety
+///
+/// `ptr` must be null or valid for writes of `len` bytes.
+unsafe fn output<'a>(ptr: *mut u8, len: usize, expected: usize) -> Result<&'a mut [u8]> {
+    if ptr.is_null() {
+        return Err(HybridKemStatus::NullPointer);
+    }
+    if len != expected {
+        return Err(HybridKemStatus::InvalidLength);
+    }
+    Ok(unsafe { slice::from_raw_parts_mut(ptr, len) })
+}
+
+fn kem(algorithm: u32) -> Result<&'static dyn DynHybridKem> {
+    let algorithm = HybridKemAlgorithm::from_u32(algorithm).ok_or(HybridKemStatus::InvalidInput)?;
+    Ok(Algorithm::from(algorithm).kem())
+}
+
+/// The size of the seed for `hybrid_kem_keygen_derand`, or 0 for an unknown algorithm
+#[unsafe(no_mangle)]
+pub extern "C" fn hybrid_kem_seed_size(algorithm: u32) -> usize {
+    kem(algorithm).map_or(0, |kem| kem.seed_size())
+}
+
+/// The size of an encapsulation key, or 0 for an unknown algorithm
+#[unsafe(no_mangle)]
+pub extern "C" fn hybrid_kem_encapsulation_key_size(algorithm: u32) -> usize {
+    kem(algorithm).map_or(0, |kem| kem.encapsulation_key_size())
+}
+
+/// The size of a decapsulation key, or 0 for an unknown algorithm
+#[unsafe(no_mangle)]
+pub extern "C" fn hybrid_kem_decapsulation_key_size(algorithm: u32) -> usize {
+    kem(algorithm).map_or(0, |kem| kem.decapsulation_key_size())
+}
+
+/// The size of a ciphertext, or 0 for an unknown algorithm
+#[unsafe(no_mangle)]
+pub extern "C" fn hybrid_kem_ciphertext_size(algorithm: u32) -> usize {
+    kem(algorithm).map_or(0, |kem| kem.ciphertext_size())
+}
+
+/// The size of a shared secret, or 0 for an unknown algorithm
+#[unsafe(no_mangle)]
+pub extern "C" fn hybrid_kem_shared_secret_size(algorithm: u32) -> usize {
+    kem(algorithm).map_or(0, |kem| kem.shared_secret_size())
+}
+
+/// The size of the randomness for `hybrid_kem_encaps_derand`, or 0 for an unknown algorithm
+#[unsafe(no_mangle)]
+pub extern "C" fn hybrid_kem_randomness_size(algorithm: u32) -> usize {
+    kem(algorithm).map_or(0, |kem| kem.randomness_size())
+}
+
+/// Derive a key pair from a seed
+///
+/// # Safety
+///
+/// Each pointer must be null or valid for as many bytes as the length that follows it, and the
+/// outputs must not overlap.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hybrid_kem_keygen_derand(
+    algorithm: u32,
+    seed: *const u8,
+    seed_len: usize,
+    dk: *mut u8,
+    dk_len: usize,
+    ek: *mut u8,
+    ek_len: usize,
+) -> HybridKemStatus {
+    status(|| {
+        let kem = kem(algorithm)?;
+        let seed = unsafe { input(seed, seed_len)? };
+        let dk = unsafe { output(dk, dk_len, kem.decapsulation_key_size())? };
+        let ek = unsafe { output(ek, ek_len, kem.encapsulation_key_size())? };
+
+        let (dk_out, ek_out) = kem.derive_key_pair(seed)?;
+        dk.copy_from_slice(&dk_out);
+        ek.copy_from_slice(&ek_out);
+        Ok(())
+    })
+}
+
+/// Encapsulate to an encapsulation key, using the operating system's random number generator
+///
+/// # Safety
+///
+/// Each pointer must be null or valid for as many bytes as the length that follows it, and the
+/// outputs must not overlap.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hybrid_kem_encaps(
+    algorithm: u32,
+    ek: *const u8,
+    ek_len: usize,
+    ct: *mut u8,
+    ct_len: usize,
+    ss: *mut u8,
+    ss_len: usize,
+) -> HybridKemStatus {
+    status(|| {
+        let kem = kem(algorithm)?;
+        let ek = unsafe { input(ek, ek_len)? };
+        let ct = unsafe { output(ct, ct_len, kem.ciphertext_size())? };
+        let ss = unsafe { output(ss, ss_len, kem.shared_secret_size())? };
+
+        let (ss_out, ct_out) = kem.encaps(ek, &mut rand::rng())?;
+        ct.copy_from_slice(&ct_out);
+        ss.copy_from_slice(&ss_out);
+        Ok(())
+    })
+}
+
+/// Encapsulate to an encapsulation key with the given randomness
+///
+/// # Safety
+///
+/// Each pointer must be null or valid for as many bytes as the length that follows it, and the
+/// outputs must not overlap.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hybrid_kem_encaps_derand(
+    algorithm: u32,
+    ek: *const u8,
+    ek_len: usize,
+    randomness: *const u8,
+    randomness_len: usize,
+    ct: *mut u8,
+    ct_len: usize,
+    ss: *mut u8,
+    ss_len: usize,
+) -> HybridKemStatus {
+    status(|| {
+        let kem = kem(algorithm)?;
+        let ek = unsafe { input(ek, ek_len)? };
+        let randomness = unsafe { input(randomness, randomness_len)? };
+        let ct = unsafe { output(ct, ct_len, kem.ciphertext_size())? };
+        let ss = unsafe { output(ss, ss_len, kem.shared_secret_size())? };
+
+        let (ct_out, ss_out) = kem.encaps_derand(ek, randomness)?;
+        ct.copy_from_slice(&ct_out);
+        ss.copy_from_slice(&ss_out);
+        Ok(())
+    })
+}
+
+/// Decapsulate a ciphertext
+///
+/// # Safety
+///
+/// Each pointer must be null or valid for as many bytes as the length that follows it.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn hybrid_kem_decaps(
+    algorithm: u32,
+    dk: *const u8,
+    dk_len: usize,
+    ct: *const u8,
+    ct_len: usize,
+    ss: *mut u8,
+    ss_len: usize,
+) -> HybridKemStatus {
+    status(|| {
+        let kem = kem(algorithm)?;
+        let dk = unsafe { input(dk, dk_len)? };
+        let ct = unsafe { input(ct, ct_len)? };
+        let ss = unsafe { output(ss, ss_len, kem.shared_secret_size())? };
+
+        let ss_out = kem.decaps(dk, ct)?;
+        ss.copy_from_slice(&ss_out);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn matches_rust_api() {
+        for algorithm in HybridKemAlgorithm::ALL {
+            let algorithm = algorithm as u32;
+            let kem = kem(algorithm).unwrap();
+            let seed = vec![0x5a; hybrid_kem_seed_size(algorithm)];
+            let randomness = vec![0xa5; hybrid_kem_randomness_size(algorithm)];
+            let mut dk = vec![0; hybrid_kem_decapsulation_key_size(algorithm)];
+            let mut ek = vec![0; hybrid_kem_encapsulation_key_size(algorithm)];
+            let mut ct = vec![0; hybrid_kem_ciphertext_size(algorithm)];
+            let mut ss = vec![0; hybrid_kem_shared_secret_size(algorithm)];
+            let mut ss_decaps = vec![0; ss.len()];
+
+            unsafe {
+                let status = hybrid_kem_keygen_derand(
+                    algorithm,
+                    seed.as_ptr(),
+                    seed.len(),
+                    dk.as_mut_ptr(),
+                    dk.len(),
+                    ek.as_mut_ptr(),
+                    ek.len(),
+                );
+                assert_eq!(status, HybridKemStatus::Ok);
+
+                let status = hybrid_kem_encaps_derand(
+                    algorithm,
+                    ek.as_ptr(),
+                    ek.len(),
+                    randomness.as_ptr(),
+                    randomness.len(),
+                    ct.as_mut_ptr(),
+                    ct.len(),
+                    ss.as_mut_ptr(),
+                    ss.len(),
+                );
+                assert_eq!(status, HybridKemStatus::Ok);
+
+                let status = hybrid_kem_decaps(
+                    algorithm,
+                    dk.as_ptr(),
+                    dk.len(),
+                    ct.as_ptr(),
+                    ct.len(),
+                    ss_decaps.as_mut_ptr(),
+                    ss_decaps.len(),
+                );
+                assert_eq!(status, HybridKemStatus::Ok);
+            }
+
+            let (dk_rust, ek_rust) = kem.derive_key_pair(&seed).unwrap();
+            let (ct_rust, ss_rust) = kem.encaps_derand(&ek_rust, &randomness).unwrap();
+            assert_eq!(dk, dk_rust);
+            assert_eq!(ek, ek_rust);
+            assert_eq!(ct, ct_rust);
+            assert_eq!(ss, ss_rust);
+            assert_eq!(ss_decaps, ss_rust);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let algorithm = HybridKemAlgorithm::MlKem768P256 as u32;
+        let seed = [0; 32];
+        let mut dk = [0; 32];
+        let mut ek = vec![0; hybrid_kem_encapsulation_key_size(algorithm)];
+        let mut ss = [0; 32];
+
+        unsafe {
+            // Null pointers
+            let status = hybrid_kem_keygen_derand(
+                algorithm,
+                ptr::null(),
+                32,
+                dk.as_mut_ptr(),
+                32,
+                ek.as_mut_ptr(),
+                ek.len(),
+            );
+            assert_eq!(status, HybridKemStatus::NullPointer);
+
+            // Wrong input and output lengths
+            let status = hybrid_kem_keygen_derand(
+                algorithm,
+                seed.as_ptr(),
+                31,
+                dk.as_mut_ptr(),
+                32,
+                ek.as_mut_ptr(),
+                ek.len(),
+            );
+            assert_eq!(status, HybridKemStatus::InvalidLength);
+            let status = hybrid_kem_keygen_derand(
+                algorithm,
+                seed.as_ptr(),
+                32,
+                dk.as_mut_ptr(),
+                32,
+                ek.as_mut_ptr(),
+                ek.len() - 1,
+            );
+            assert_eq!(status, HybridKemStatus::InvalidLength);
+
+            // An invalid point in the traditional ciphertext
+            let ct = vec![0xff; hybrid_kem_ciphertext_size(algorithm)];
+            let status = hybrid_kem_decaps(
+                algorithm,
+                dk.as_ptr(),
+                32,
+                ct.as_ptr(),
+                ct.len(),
+                ss.as_mut_ptr(),
+                32,
+            );
+            assert_eq!(status, HybridKemStatus::InvalidInput);
+        }
+
+        // Nothing was written on failure
+        assert_eq!(dk, [0; 32]);
+        assert_eq!(ss, [0; 32]);
+    }
+
+    // C callers can pass any value where an algorithm is expected
+    #[test]
+    fn unknown_algorithm() {
+        let seed = [0; 32];
+        let mut dk = [0; 32];
+        let mut ek = [0; 1216];
+
+        assert_eq!(hybrid_kem_seed_size(3), 0);
+        assert_eq!(hybrid_kem_ciphertext_size(u32::MAX), 0);
+        let status = unsafe {
+            hybrid_kem_keygen_derand(
+                3,
+                seed.as_ptr(),
+                32,
+                dk.as_mut_ptr(),
+                32,
+                ek.as_mut_ptr(),
+                ek.len(),
+            )
+        };
+        assert_eq!(status, HybridKemStatus::InvalidInput);
+        assert_eq!(dk, [0; 32]);
+    }
+
+    // The checked-in header matches the code.  Regenerate it with
+    // `UPDATE_HEADER=1 cargo test -p concrete-hybrid-kem-ffi header`.
+    #[test]
+    fn header() {
+        let crate_dir = env!("CARGO_MANIFEST_DIR");
+        let path = std::path::Path::new(crate_dir).join("include/concrete_hybrid_kem.h");
+        let bindings = cbindgen::generate(crate_dir).expect("Unable to generate the C header");
+
+        if std::env::var_os("UPDATE_HEADER").is_some() {
+            bindings.write_to_file(&path);
+        }
+        let mut generated = Vec::new();
+        bindings.write(&mut generated);
+        let checked_in = std::fs::read(&path).unwrap();
+        assert!(
+            generated == checked_in,
+            "{} is out of date; regenerate it with UPDATE_HEADER=1",
+            path.display()
+        );
+    }
+}
@@ -0,0 +1,39 @@
+//! Compile `roundtrip.c` against the static library and the generated header, and run it
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn roundtrip() {
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    // Cargo builds the staticlib for the test next to the test executable, in target/<profile>/deps
+    let exe = env::current_exe().unwrap();
+    let deps_dir = exe.parent().unwrap();
+    let staticlib = deps_dir.join("libconcrete_hybrid_kem_ffi.a");
+    assert!(staticlib.exists(), "missing {}", staticlib.display());
+
+    let program = deps_dir.join("ffi-roundtrip");
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(cc)
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror"])
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/roundtrip.c"))
+        .arg(&staticlib)
+        .args(["-lpthread", "-ldl", "-lm"])
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("failed to run the C compiler");
+    assert!(status.success(), "compiling roundtrip.c failed");
+
+    let output = Command::new(&program).output().unwrap();
+    assert!(
+        output.status.success(),
+        "roundtrip.c failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(output.stdout, b"ok\n");
+}
@@ -0,0 +1,80 @@
+/* Round trip through the C interface for every instance */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "concrete_hybrid_kem.h"
+
+#define CHECK(expr)                                                        \
+  do {                                                                     \
+    if (!(expr)) {                                                         \
+      fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #expr); \
+      return 1;                                                            \
+    }                                                                      \
+  } while (0)
+
+static int roundtrip(HybridKemAlgorithm algorithm) {
+  size_t seed_len = hybrid_kem_seed_size(algorithm);
+  size_t dk_len = hybrid_kem_decapsulation_key_size(algorithm);
+  size_t ek_len = hybrid_kem_encapsulation_key_size(algorithm);
+  size_t ct_len = hybrid_kem_ciphertext_size(algorithm);
+  size_t ss_len = hybrid_kem_shared_secret_size(algorithm);
+  size_t randomness_len = hybrid_kem_randomness_size(algorithm);
+
+  uint8_t seed[64], dk[64], ek[2048], ct[2048], randomness[128];
+  uint8_t ss_encaps[64], ss_decaps[64], ss_derand[64];
+  CHECK(seed_len <= sizeof(seed) && dk_len <= sizeof(dk));
+  CHECK(ek_len <= sizeof(ek) && ct_len <= sizeof(ct));
+  CHECK(ss_len <= sizeof(ss_encaps) && randomness_len <= sizeof(randomness));
+
+  memset(seed, 0x5a, seed_len);
+  memset(randomness, 0xa5, randomness_len);
+
+  CHECK(hybrid_kem_keygen_derand(algorithm, seed, seed_len, dk, dk_len, ek, ek_len) ==
+        HYBRID_KEM_STATUS_OK);
+
+  /* Randomized encapsulation */
+  CHECK(hybrid_kem_encaps(algorithm, ek, ek_len, ct, ct_len, ss_encaps, ss_len) ==
+        HYBRID_KEM_STATUS_OK);
+  CHECK(hybrid_kem_decaps(algorithm, dk, dk_len, ct, ct_len, ss_decaps, ss_len) ==
+        HYBRID_KEM_STATUS_OK);
+  CHECK(memcmp(ss_encaps, ss_decaps, ss_len) == 0);
+
+  /* Derandomized encapsulation */
+  CHECK(hybrid_kem_encaps_derand(algorithm, ek, ek_len, randomness, randomness_len, ct, ct_len,
+                                 ss_derand, ss_len) == HYBRID_KEM_STATUS_OK);
+  CHECK(hybrid_kem_decaps(algorithm, dk, dk_len, ct, ct_len, ss_decaps, ss_len) ==
+        HYBRID_KEM_STATUS_OK);
+  CHECK(memcmp(ss_derand, ss_decaps, ss_len) == 0);
+
+  /* Errors are reported, not raised */
+  CHECK(hybrid_kem_decaps(algorithm, NULL, dk_len, ct, ct_len, ss_decaps, ss_len) ==
+        HYBRID_KEM_STATUS_NULL_POINTER);
+  CHECK(hybrid_kem_decaps(algorithm, dk, dk_len, ct, ct_len - 1, ss_decaps, ss_len) ==
+        HYBRID_KEM_STATUS_INVALID_LENGTH);
+
+  return 0;
+}
+
+int main(void) {
+  const HybridKemAlgorithm algorithms[] = {
+      HYBRID_KEM_ALGORITHM_ML_KEM768_P256,
+      HYBRID_KEM_ALGORITHM_ML_KEM768_X25519,
+      HYBRID_KEM_ALGORITHM_ML_KEM1024_P384,
+  };
+
+  for (size_t i = 0; i < sizeof(algorithms) / sizeof(algorithms[0]); i++) {
+    if (roundtrip(algorithms[i]) != 0) {
+      fprintf(stderr, "round trip failed for algorithm %d\n", (int)algorithms[i]);
+      return 1;
+    }
+  }
+
+  /* Values outside the enum are rejected */
+  CHECK(hybrid_kem_seed_size(3) == 0);
+  CHECK(hybrid_kem_decaps(3, NULL, 0, NULL, 0, NULL, 0) == HYBRID_KEM_STATUS_INVALID_INPUT);
+
+  printf("ok\n");
+  return 0;
+}
@@ -0,0 +1,62 @@
+//! Decapsulation of arbitrary ciphertexts under arbitrary keys, for each instance
+//!
+//! The input is a selector for the instance and the mode, the decapsulation key, and the
+//! ciphertext.  Decapsulation goes through the runtime `DynHybridKem` interface, which must accept
+//! a ciphertext exactly when `InputValidation::validate_ciphertext` does, and must never panic.
+//! The modes are:
+//!
+//! * raw: the ciphertext is the rest of the input, of any length
+//! * padded: the ciphertext is padded or truncated to the ciphertext size
+//! * derived: as padded, with the traditional ciphertext replaced by a valid element derived from
+//!   the rest of the input, so that the post-quantum ciphertext is exercised on the NIST curves
+//!
+//! Decapsulation must be deterministic, and by implicit rejection it must produce a shared secret
+//! for any ciphertext that passes validation.
+
+#![no_main]
+
+use concrete_hybrid_kem::algorithm::Algorithm;
+use concrete_hybrid_kem::group::{P256, P384, X25519};
+use concrete_hybrid_kem::hybrid::HybridKem;
+use concrete_hybrid_kem::{MlKem768P256, MlKem768X25519, MlKem1024P384};
+use concrete_hybrid_kem_fuzz::{KnownPanics, take, valid_element};
+use libfuzzer_sys::fuzz_target;
+
+fn decaps<K: HybridKem, G: KnownPanics>(algorithm: Algorithm, mode: u8, mut data: &[u8]) {
+    let kem = algorithm.kem();
+
+    let dk = take(&mut data, K::DECAPSULATION_KEY_SIZE);
+    let ct = match mode {
+        0 => data.to_vec(),
+        1 => take(&mut data, K::CIPHERTEXT_SIZE),
+        _ => {
+            let mut ct = take(&mut data, K::CIPHERTEXT_SIZE);
+            let ct_pq_size = K::CIPHERTEXT_SIZE - G::ELEMENT_SIZE;
+            ct[ct_pq_size..].copy_from_slice(&valid_element::<G>(&mut data));
+            assert!(K::validate_ciphertext(&ct));
+            ct
+        }
+    };
+
+    let result = kem.decaps(&dk, &ct);
+    assert_eq!(result.is_ok(), K::validate_ciphertext(&ct));
+
+    if let Ok(ss) = result {
+        assert_eq!(ss.len(), K::SHARED_SECRET_SIZE);
+        assert_eq!(Ok(ss), kem.decaps(&dk, &ct));
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, data)) = data.split_first() else {
+        return;
+    };
+
+    let mode = (selector / 3) % 3;
+
+    match selector % 3 {
+        0 => decaps::<MlKem768P256, P256>(Algorithm::MlKem768P256, mode, data),
+        1 => decaps::<MlKem768X25519, X25519>(Algorithm::MlKem768X25519, mode, data),
+        _ => decaps::<MlKem1024P384, P384>(Algorithm::MlKem1024P384, mode, data),
+    }
+});
@@ -0,0 +1,62 @@
+//! Encapsulation to arbitrary and to derived encapsulation keys, for each instance
+//!
+//! The input is an instance selector, a mode byte, the encapsulation randomness, and either a
+//! key generation seed or an encapsulation key, which is the rest of the input and may have any
+//! length.  Encapsulation goes through the runtime `DynHybridKem` interface, which must accept an
+//! encapsulation key exactly when `InputValidation::validate_encapsulation_key` does, and must
+//! never panic.  Encapsulation must be deterministic in its randomness, and for derived keys
+//! decapsulation must recover the shared secret.
+
+#![no_main]
+
+use concrete_hybrid_kem::algorithm::Algorithm;
+use concrete_hybrid_kem::hybrid::HybridKem;
+use concrete_hybrid_kem::{MlKem768P256, MlKem768X25519, MlKem1024P384};
+use concrete_hybrid_kem_fuzz::take;
+use libfuzzer_sys::fuzz_target;
+
+fn encaps<K: HybridKem>(algorithm: Algorithm, derived: bool, mut data: &[u8]) {
+    let kem = algorithm.kem();
+    let randomness = take(&mut data, K::RANDOMNESS_SIZE);
+
+    let (dk, ek) = if derived {
+        let (dk, ek) = kem.derive_key_pair(&take(&mut data, K::SEED_SIZE)).unwrap();
+        assert!(K::validate_encapsulation_key(&ek));
+        (Some(dk), ek)
+    } else {
+        (None, data.to_vec())
+    };
+
+    let result = kem.encaps_derand(&ek, &randomness);
+    assert_eq!(result.is_ok(), K::validate_encapsulation_key(&ek));
+
+    let Ok((ct, ss)) = result else {
+        return;
+    };
+    assert_eq!(ct.len(), K::CIPHERTEXT_SIZE);
+    assert_eq!(ss.len(), K::SHARED_SECRET_SIZE);
+    assert_eq!(
+        Ok((ct.clone(), ss.clone())),
+        kem.encaps_derand(&ek, &randomness)
+    );
+
+    if let Some(dk) = dk {
+        assert_eq!(kem.decaps(&dk, &ct), Ok(ss));
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, data)) = data.split_first() else {
+        return;
+    };
+    let Some((&mode, data)) = data.split_first() else {
+        return;
+    };
+    let derived = mode & 1 == 0;
+
+    match selector % 3 {
+        0 => encaps::<MlKem768P256>(Algorithm::MlKem768P256, derived, data),
+        1 => encaps::<MlKem768X25519>(Algorithm::MlKem768X25519, derived, data),
+        _ => encaps::<MlKem1024P384>(Algorithm::MlKem1024P384, derived, data),
+    }
+});
@@ -0,0 +1,49 @@
+//! `NominalGroup::exp` on arbitrary elements and scalars, for each group
+//!
+//! The input is a group selector, an element, and two scalars.  If the selector is odd, the
+//! element is derived from the input instead, so that valid NIST curve points are exercised.
+//! Exponentiation must produce an element of the right size, and exponentiation by the two
+//! scalars must commute.
+
+#![no_main]
+
+use concrete_hybrid_kem::group::{P256, P384, X25519};
+use concrete_hybrid_kem_fuzz::{KnownPanics, take, valid_element};
+use libfuzzer_sys::fuzz_target;
+
+fn exp<G: KnownPanics>(derive_element: bool, mut data: &[u8]) {
+    let element = if derive_element {
+        valid_element::<G>(&mut data)
+    } else {
+        take(&mut data, G::ELEMENT_SIZE)
+    };
+    let a = take(&mut data, G::SCALAR_SIZE);
+    let b = take(&mut data, G::SCALAR_SIZE);
+
+    // Known panics: an invalid element, or a result that is the identity
+    if !G::validate_element(&element) || G::zero_scalar(&a) || G::zero_scalar(&b) {
+        return;
+    }
+
+    let element_a = G::exp(&element, &a);
+    let element_b = G::exp(&element, &b);
+    assert_eq!(element_a.len(), G::ELEMENT_SIZE);
+    assert_eq!(G::exp(&element_a, &b), G::exp(&element_b, &a));
+
+    let ss = G::element_to_shared_secret(&element_a);
+    assert_eq!(ss.len(), G::SHARED_SECRET_SIZE);
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, data)) = data.split_first() else {
+        return;
+    };
+
+    let derive_element = selector & 1 == 1;
+
+    match (selector >> 1) % 3 {
+        0 => exp::<P256>(derive_element, data),
+        1 => exp::<X25519>(derive_element, data),
+        _ => exp::<P384>(derive_element, data),
+    }
+});
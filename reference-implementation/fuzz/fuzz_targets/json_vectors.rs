@@ -0,0 +1,23 @@
+//! Parsing of test vectors in the format of `test-vectors.json`
+//!
+//! Parsing must not panic, and anything that parses must survive serialization unchanged.
+
+#![no_main]
+
+use concrete_hybrid_kem::test_vectors::TestVectors;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(test_vectors) = serde_json::from_str::<TestVectors>(text) else {
+        return;
+    };
+
+    let json = serde_json::to_string(&test_vectors).unwrap();
+    assert_eq!(
+        serde_json::from_str::<TestVectors>(&json).unwrap(),
+        test_vectors
+    );
+});
@@ -0,0 +1,19 @@
+//! Parsing of test vectors in the text format of the files in `spec/`
+//!
+//! Parsing must not panic, and anything that parses must survive rendering unchanged.
+
+#![no_main]
+
+use concrete_hybrid_kem::test_vectors::{from_spec_text, to_spec_text};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(vectors) = from_spec_text(text) else {
+        return;
+    };
+
+    assert_eq!(from_spec_text(&to_spec_text(&vectors)), Ok(vectors));
+});
@@ -0,0 +1,59 @@
+//! Helpers shared by the fuzz targets
+//!
+//! The `decaps` and `encaps` targets go through the runtime `DynHybridKem` interface, which
+//! reports invalid inputs as errors, so any panic there is a bug.  The `group_exp` target calls
+//! `NominalGroup::exp` directly, which panics on malformed elements and on scalars that map to the
+//! identity; it skips the inputs that `NominalGroup::validate_element` rejects or that
+//! `KnownPanics` identifies, and any other panic is a bug.
+
+use concrete_hybrid_kem::group::{NominalGroup, P256, P384, X25519};
+
+/// The inputs besides invalid elements on which the group operations are known to panic
+pub trait KnownPanics: NominalGroup {
+    /// Whether `exp` by `scalar` maps every element to the identity, which has no encoding of
+    /// `ELEMENT_SIZE` bytes
+    fn zero_scalar(scalar: &[u8]) -> bool;
+}
+
+impl KnownPanics for X25519 {
+    // Clamping keeps the scalar nonzero
+    fn zero_scalar(_scalar: &[u8]) -> bool {
+        false
+    }
+}
+
+macro_rules! impl_known_panics {
+    ($group:ident, $mod:ident) => {
+        impl KnownPanics for $group {
+            fn zero_scalar(scalar: &[u8]) -> bool {
+                use $mod::elliptic_curve::{Field, ops::Reduce};
+                use $mod::{FieldBytes, Scalar};
+
+                Field::is_zero(&Scalar::reduce_bytes(FieldBytes::from_slice(scalar))).into()
+            }
+        }
+    };
+}
+
+impl_known_panics!(P256, p256);
+impl_known_panics!(P384, p384);
+
+/// Take `len` bytes from the front of `data`, padding with zeros if it runs out.  Padding lets
+/// the fuzzer reach the fixed sizes of keys and ciphertexts without having to guess them.
+pub fn take(data: &mut &[u8], len: usize) -> Vec<u8> {
+    let n = len.min(data.len());
+    let mut value = data[..n].to_vec();
+    value.resize(len, 0);
+    *data = &data[n..];
+    value
+}
+
+/// A valid element derived from `data`, since arbitrary bytes are almost never a point on the
+/// NIST curves
+pub fn valid_element<G: KnownPanics>(data: &mut &[u8]) -> Vec<u8> {
+    let mut scalar = take(data, G::SCALAR_SIZE);
+    if G::zero_scalar(&scalar) {
+        scalar[G::SCALAR_SIZE - 1] = 1;
+    }
+    G::exp(&G::generator(), &scalar)
+}
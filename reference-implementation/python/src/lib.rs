@@ -0,0 +1,207 @@
+//! Python bindings for the concrete hybrid KEMs
+//!
+//! The extension module `concrete_hybrid_kem` has a class for each instance, with the sizes as
+//! class attributes and the operations as static methods on `bytes`.  Inputs of the wrong length
+//! and inputs that cannot be processed (e.g., an invalid curve point) raise `ValueError`.  Both
+//! `encaps` and `encaps_derand` return `(ct, ss)`.  The module also loads `test-vectors.json`.
+//! Build it with `maturin develop` in this directory.
+
+use concrete_hybrid_kem::algorithm::{self, Algorithm};
+use concrete_hybrid_kem::kem::{EncapsDerand, Kem, SeedSize, SharedSecretSize};
+use concrete_hybrid_kem::test_vectors::{HybridKemTestVector, TestVectors};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList};
+use std::fs;
+use std::path::PathBuf;
+
+type BytesPair<'py> = (Bound<'py, PyBytes>, Bound<'py, PyBytes>);
+
+/// Raise an operation's error as `ValueError`
+fn value_error(err: algorithm::Error) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+fn derive_key_pair<'py>(
+    py: Python<'py>,
+    algorithm: Algorithm,
+    seed: &[u8],
+) -> PyResult<BytesPair<'py>> {
+    let (dk, ek) = algorithm.kem().derive_key_pair(seed).map_err(value_error)?;
+    Ok((PyBytes::new(py, &dk), PyBytes::new(py, &ek)))
+}
+
+fn encaps<'py>(py: Python<'py>, algorithm: Algorithm, ek: &[u8]) -> PyResult<BytesPair<'py>> {
+    let (ss, ct) = algorithm
+        .kem()
+        .encaps(ek, &mut rand::rng())
+        .map_err(value_error)?;
+    Ok((PyBytes::new(py, &ct), PyBytes::new(py, &ss)))
+}
+
+fn encaps_derand<'py>(
+    py: Python<'py>,
+    algorithm: Algorithm,
+    ek: &[u8],
+    randomness: &[u8],
+) -> PyResult<BytesPair<'py>> {
+    let (ct, ss) = algorithm
+        .kem()
+        .encaps_derand(ek, randomness)
+        .map_err(value_error)?;
+    Ok((PyBytes::new(py, &ct), PyBytes::new(py, &ss)))
+}
+
+fn decaps<'py>(
+    py: Python<'py>,
+    algorithm: Algorithm,
+    dk: &[u8],
+    ct: &[u8],
+) -> PyResult<Bound<'py, PyBytes>> {
+    let ss = algorithm.kem().decaps(dk, ct).map_err(value_error)?;
+    Ok(PyBytes::new(py, &ss))
+}
+
+macro_rules! define_instance {
+    ($name:ident, $kem:ty, $algorithm:expr, $display_name:literal) => {
+        #[pyclass(frozen, module = "concrete_hybrid_kem")]
+        pub struct $name;
+
+        #[pymethods]
+        impl $name {
+            /// The name of the instance in the draft
+            #[classattr]
+            const NAME: &'static str = $display_name;
+
+            #[classattr]
+            const SEED_SIZE: usize = <$kem as SeedSize>::SEED_SIZE;
+
+            #[classattr]
+            const ENCAPSULATION_KEY_SIZE: usize = <$kem as Kem>::ENCAPSULATION_KEY_SIZE;
+
+            #[classattr]
+            const DECAPSULATION_KEY_SIZE: usize = <$kem as Kem>::DECAPSULATION_KEY_SIZE;
+
+            #[classattr]
+            const CIPHERTEXT_SIZE: usize = <$kem as Kem>::CIPHERTEXT_SIZE;
+
+            #[classattr]
+            const SHARED_SECRET_SIZE: usize = <$kem as SharedSecretSize>::SHARED_SECRET_SIZE;
+
+            #[classattr]
+            const RANDOMNESS_SIZE: usize = <$kem as EncapsDerand>::RANDOMNESS_SIZE;
+
+            /// Derive a key pair from a seed, returning `(dk, ek)`
+            #[staticmethod]
+            fn derive_key_pair<'py>(py: Python<'py>, seed: &[u8]) -> PyResult<BytesPair<'py>> {
+                derive_key_pair(py, $algorithm, seed)
+            }
+
+            /// Encapsulate with randomness from the operating system, returning `(ct, ss)`
+            #[staticmethod]
+            fn encaps<'py>(py: Python<'py>, ek: &[u8]) -> PyResult<BytesPair<'py>> {
+                encaps(py, $algorithm, ek)
+            }
+
+            /// Encapsulate with the given randomness, returning `(ct, ss)`
+            #[staticmethod]
+            fn encaps_derand<'py>(
+                py: Python<'py>,
+                ek: &[u8],
+                randomness: &[u8],
+            ) -> PyResult<BytesPair<'py>> {
+                encaps_derand(py, $algorithm, ek, randomness)
+            }
+
+            /// Decapsulate a ciphertext, returning `ss`
+            #[staticmethod]
+            fn decaps<'py>(py: Python<'py>, dk: &[u8], ct: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+                decaps(py, $algorithm, dk, ct)
+            }
+        }
+    };
+}
+
+define_instance!(
+    MlKem768P256,
+    concrete_hybrid_kem::MlKem768P256,
+    Algorithm::MlKem768P256,
+    "MLKEM768-P256"
+);
+define_instance!(
+    MlKem768X25519,
+    concrete_hybrid_kem::MlKem768X25519,
+    Algorithm::MlKem768X25519,
+    "MLKEM768-X25519"
+);
+define_instance!(
+    MlKem1024P384,
+    concrete_hybrid_kem::MlKem1024P384,
+    Algorithm::MlKem1024P384,
+    "MLKEM1024-P384"
+);
+
+fn vector_to_dict<'py>(
+    py: Python<'py>,
+    vector: &HybridKemTestVector,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    let fields: [(&str, &[u8]); 8] = [
+        ("seed", &vector.seed),
+        ("randomness", &vector.randomness),
+        ("encapsulation_key", &vector.encapsulation_key),
+        ("decapsulation_key", &vector.decapsulation_key),
+        ("decapsulation_key_pq", &vector.decapsulation_key_pq),
+        ("decapsulation_key_t", &vector.decapsulation_key_t),
+        ("ciphertext", &vector.ciphertext),
+        ("shared_secret", &vector.shared_secret),
+    ];
+    for (name, value) in fields {
+        dict.set_item(name, PyBytes::new(py, value))?;
+    }
+    Ok(dict)
+}
+
+/// Parse test vectors in the format of `test-vectors.json`, returning a dict from instance name
+/// to a list of vectors, each a dict from field name to `bytes`
+#[pyfunction]
+fn parse_test_vectors<'py>(py: Python<'py>, json: &str) -> PyResult<Bound<'py, PyDict>> {
+    let test_vectors: TestVectors =
+        serde_json::from_str(json).map_err(|err| PyValueError::new_err(err.to_string()))?;
+
+    let result = PyDict::new(py);
+    for algorithm in Algorithm::ALL {
+        let vectors = test_vectors
+            .get(algorithm)
+            .iter()
+            .map(|vector| vector_to_dict(py, vector))
+            .collect::<PyResult<Vec<_>>>()?;
+        result.set_item(algorithm.name(), PyList::new(py, vectors)?)?;
+    }
+    Ok(result)
+}
+
+/// Load test vectors from a file such as `test-vectors.json`, as `parse_test_vectors` does
+#[pyfunction]
+fn load_test_vectors<'py>(py: Python<'py>, path: PathBuf) -> PyResult<Bound<'py, PyDict>> {
+    let json = fs::read_to_string(path)?;
+    parse_test_vectors(py, &json)
+}
+
+#[pymodule]
+#[pyo3(name = "concrete_hybrid_kem")]
+fn extension_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<MlKem768P256>()?;
+    m.add_class::<MlKem768X25519>()?;
+    m.add_class::<MlKem1024P384>()?;
+    m.add_function(wrap_pyfunction!(parse_test_vectors, m)?)?;
+    m.add_function(wrap_pyfunction!(load_test_vectors, m)?)?;
+
+    // The classes by instance name, matching the keys of the test vector dicts
+    let instances = PyDict::new(m.py());
+    instances.set_item(MlKem768P256::NAME, m.getattr("MlKem768P256")?)?;
+    instances.set_item(MlKem768X25519::NAME, m.getattr("MlKem768X25519")?)?;
+    instances.set_item(MlKem1024P384::NAME, m.getattr("MlKem1024P384")?)?;
+    m.add("INSTANCES", instances)?;
+    Ok(())
+}
@@ -0,0 +1,89 @@
+"""Tests for the Python bindings, including the checked-in test vectors.
+
+Run with `python -m unittest discover tests` (or `pytest`) after `maturin develop`.
+"""
+
+import unittest
+from pathlib import Path
+
+import concrete_hybrid_kem
+from concrete_hybrid_kem import INSTANCES, MlKem768P256, MlKem768X25519, MlKem1024P384
+
+TEST_VECTORS = Path(__file__).resolve().parents[3] / "test-vectors.json"
+
+
+class TestVectors(unittest.TestCase):
+    def test_checked_in_vectors(self):
+        test_vectors = concrete_hybrid_kem.load_test_vectors(TEST_VECTORS)
+        self.assertEqual(set(test_vectors), set(INSTANCES))
+
+        for name, vectors in test_vectors.items():
+            kem = INSTANCES[name]
+            self.assertTrue(vectors, name)
+            for i, v in enumerate(vectors):
+                with self.subTest(instance=name, vector=i):
+                    dk, ek = kem.derive_key_pair(v["seed"])
+                    self.assertEqual(dk, v["decapsulation_key"])
+                    self.assertEqual(ek, v["encapsulation_key"])
+
+                    ct, ss = kem.encaps_derand(ek, v["randomness"])
+                    self.assertEqual(ct, v["ciphertext"])
+                    self.assertEqual(ss, v["shared_secret"])
+
+                    self.assertEqual(kem.decaps(dk, ct), v["shared_secret"])
+
+    def test_parse(self):
+        text = TEST_VECTORS.read_text()
+        self.assertEqual(
+            concrete_hybrid_kem.parse_test_vectors(text),
+            concrete_hybrid_kem.load_test_vectors(str(TEST_VECTORS)),
+        )
+
+        # Instances missing from the JSON have no vectors
+        self.assertEqual(concrete_hybrid_kem.parse_test_vectors("{}")["MLKEM768-X25519"], [])
+
+        with self.assertRaises(ValueError):
+            concrete_hybrid_kem.parse_test_vectors('{"mlkem768_p256": [{"seed": "zz"}]}')
+
+
+class TestInstances(unittest.TestCase):
+    def test_constants(self):
+        self.assertEqual(INSTANCES["MLKEM768-P256"], MlKem768P256)
+        self.assertEqual(INSTANCES["MLKEM768-X25519"], MlKem768X25519)
+        self.assertEqual(INSTANCES["MLKEM1024-P384"], MlKem1024P384)
+
+        self.assertEqual(MlKem768X25519.NAME, "MLKEM768-X25519")
+        self.assertEqual(MlKem768X25519.SEED_SIZE, 32)
+        self.assertEqual(MlKem768X25519.ENCAPSULATION_KEY_SIZE, 1216)
+        self.assertEqual(MlKem768X25519.DECAPSULATION_KEY_SIZE, 32)
+        self.assertEqual(MlKem768X25519.CIPHERTEXT_SIZE, 1120)
+        self.assertEqual(MlKem768X25519.SHARED_SECRET_SIZE, 32)
+        self.assertEqual(MlKem768X25519.RANDOMNESS_SIZE, 64)
+
+    def test_round_trip(self):
+        for name, kem in INSTANCES.items():
+            with self.subTest(instance=name):
+                dk, ek = kem.derive_key_pair(bytes(kem.SEED_SIZE))
+                self.assertEqual(len(dk), kem.DECAPSULATION_KEY_SIZE)
+                self.assertEqual(len(ek), kem.ENCAPSULATION_KEY_SIZE)
+
+                ct, ss = kem.encaps(ek)
+                self.assertEqual(len(ct), kem.CIPHERTEXT_SIZE)
+                self.assertEqual(len(ss), kem.SHARED_SECRET_SIZE)
+                self.assertEqual(kem.decaps(dk, ct), ss)
+
+    def test_errors(self):
+        with self.assertRaisesRegex(ValueError, "expected 32 bytes, got 31"):
+            MlKem768P256.derive_key_pair(bytes(31))
+
+        dk, _ = MlKem768P256.derive_key_pair(bytes(32))
+        with self.assertRaises(ValueError):
+            MlKem768P256.decaps(dk, bytes(MlKem768P256.CIPHERTEXT_SIZE - 1))
+
+        # An invalid point in the traditional ciphertext
+        with self.assertRaisesRegex(ValueError, "ciphertext: invalid value"):
+            MlKem768P256.decaps(dk, b"\xff" * MlKem768P256.CIPHERTEXT_SIZE)
+
+
+if __name__ == "__main__":
+    unittest.main()
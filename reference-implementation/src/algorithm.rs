@@ -0,0 +1,377 @@
+//! Runtime selection of hybrid KEM instances
+//!
+//! The instances in this crate are zero-sized generic types, which is convenient when the
+//! algorithm is known at compile time.  A server that negotiates the algorithm at runtime can
+//! instead parse an `Algorithm` from its name and use the object-safe `DynHybridKem` interface,
+//! which works on byte slices and reports malformed inputs as errors instead of panicking.
+
+use crate::hybrid::HybridKem;
+use crate::kem::{Ciphertext, DecapsulationKey, EncapsulationKey, SharedSecret};
+use crate::{MlKem1024P384, MlKem768P256, MlKem768X25519};
+use rand::CryptoRng;
+use std::fmt;
+use std::str::FromStr;
+
+/// An enumeration of the ways a runtime hybrid KEM operation can fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The name does not refer to a hybrid KEM in this crate
+    UnknownAlgorithm(String),
+    /// An input has the wrong length for the algorithm
+    InvalidLength {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    /// An input has the right length but is not a valid value, e.g., its traditional component is
+    /// not a point on the curve
+    InvalidInput { field: &'static str },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::UnknownAlgorithm(name) => write!(f, "unknown algorithm {:?}", name),
+            Error::InvalidLength {
+                field,
+                expected,
+                actual,
+            } => write!(f, "{}: expected {} bytes, got {}", field, expected, actual),
+            Error::InvalidInput { field } => write!(f, "{}: invalid value", field),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn check_length(field: &'static str, value: &[u8], expected: usize) -> Result<(), Error> {
+    if value.len() != expected {
+        return Err(Error::InvalidLength {
+            field,
+            expected,
+            actual: value.len(),
+        });
+    }
+    Ok(())
+}
+
+fn check_valid(field: &'static str, valid: bool) -> Result<(), Error> {
+    if !valid {
+        return Err(Error::InvalidInput { field });
+    }
+    Ok(())
+}
+
+/// Object-safe interface to a hybrid KEM.  Unlike the generic interface, which panics on
+/// malformed inputs, every operation checks the lengths of its inputs and that the traditional
+/// components of encapsulation keys and ciphertexts are group elements.
+pub trait DynHybridKem: Sync {
+    fn algorithm(&self) -> Algorithm;
+
+    fn seed_size(&self) -> usize;
+    fn encapsulation_key_size(&self) -> usize;
+    fn decapsulation_key_size(&self) -> usize;
+    fn ciphertext_size(&self) -> usize;
+    fn shared_secret_size(&self) -> usize;
+    fn randomness_size(&self) -> usize;
+
+    fn generate_key_pair(&self, rng: &mut dyn CryptoRng) -> (DecapsulationKey, EncapsulationKey);
+    fn derive_key_pair(&self, seed: &[u8]) -> Result<(DecapsulationKey, EncapsulationKey), Error>;
+    fn encaps(
+        &self,
+        ek: &[u8],
+        rng: &mut dyn CryptoRng,
+    ) -> Result<(SharedSecret, Ciphertext), Error>;
+    fn encaps_derand(
+        &self,
+        ek: &[u8],
+        randomness: &[u8],
+    ) -> Result<(Ciphertext, SharedSecret), Error>;
+    fn decaps(&self, dk: &[u8], ct: &[u8]) -> Result<SharedSecret, Error>;
+}
+
+/// Adapter from a statically typed hybrid KEM to `DynHybridKem`
+struct Dyn<K> {
+    algorithm: Algorithm,
+    _phantom: core::marker::PhantomData<K>,
+}
+
+impl<K: HybridKem> DynHybridKem for Dyn<K>
+where
+    Dyn<K>: Sync,
+{
+    fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    fn seed_size(&self) -> usize {
+        K::SEED_SIZE
+    }
+
+    fn encapsulation_key_size(&self) -> usize {
+        K::ENCAPSULATION_KEY_SIZE
+    }
+
+    fn decapsulation_key_size(&self) -> usize {
+        K::DECAPSULATION_KEY_SIZE
+    }
+
+    fn ciphertext_size(&self) -> usize {
+        K::CIPHERTEXT_SIZE
+    }
+
+    fn shared_secret_size(&self) -> usize {
+        K::SHARED_SECRET_SIZE
+    }
+
+    fn randomness_size(&self) -> usize {
+        K::RANDOMNESS_SIZE
+    }
+
+    fn generate_key_pair(&self, rng: &mut dyn CryptoRng) -> (DecapsulationKey, EncapsulationKey) {
+        let mut seed = vec![0u8; K::SEED_SIZE];
+        rng.fill_bytes(&mut seed);
+        let (dk, ek, _) = K::derive_key_pair(&seed);
+        (dk, ek)
+    }
+
+    fn derive_key_pair(&self, seed: &[u8]) -> Result<(DecapsulationKey, EncapsulationKey), Error> {
+        check_length("seed", seed, K::SEED_SIZE)?;
+        let (dk, ek, _) = K::derive_key_pair(seed);
+        Ok((dk, ek))
+    }
+
+    fn encaps(
+        &self,
+        ek: &[u8],
+        mut rng: &mut dyn CryptoRng,
+    ) -> Result<(SharedSecret, Ciphertext), Error> {
+        check_length("encapsulation key", ek, K::ENCAPSULATION_KEY_SIZE)?;
+        check_valid("encapsulation key", K::validate_encapsulation_key(ek))?;
+        Ok(K::encaps(&ek.to_vec(), &mut rng))
+    }
+
+    fn encaps_derand(
+        &self,
+        ek: &[u8],
+        randomness: &[u8],
+    ) -> Result<(Ciphertext, SharedSecret), Error> {
+        check_length("encapsulation key", ek, K::ENCAPSULATION_KEY_SIZE)?;
+        check_length("randomness", randomness, K::RANDOMNESS_SIZE)?;
+        check_valid("encapsulation key", K::validate_encapsulation_key(ek))?;
+        Ok(K::encaps_derand(&ek.to_vec(), randomness))
+    }
+
+    fn decaps(&self, dk: &[u8], ct: &[u8]) -> Result<SharedSecret, Error> {
+        check_length("decapsulation key", dk, K::DECAPSULATION_KEY_SIZE)?;
+        check_length("ciphertext", ct, K::CIPHERTEXT_SIZE)?;
+        check_valid("ciphertext", K::validate_ciphertext(ct))?;
+        Ok(K::decaps(&dk.to_vec(), &ct.to_vec()))
+    }
+}
+
+/// The hybrid KEM instances defined in this crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    MlKem768P256,
+    MlKem768X25519,
+    MlKem1024P384,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 3] = [
+        Algorithm::MlKem768P256,
+        Algorithm::MlKem768X25519,
+        Algorithm::MlKem1024P384,
+    ];
+
+    /// The name used for the instance in the draft, e.g., "MLKEM768-X25519"
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::MlKem768P256 => "MLKEM768-P256",
+            Algorithm::MlKem768X25519 => "MLKEM768-X25519",
+            Algorithm::MlKem1024P384 => "MLKEM1024-P384",
+        }
+    }
+
+    /// The composition string used to name the files in `spec/`
+    pub fn composition(self) -> &'static str {
+        match self {
+            Algorithm::MlKem768P256 => "QSF-KEM(ML-KEM-768,P-256)-XOF(SHAKE256)-KDF(SHA3-256)",
+            Algorithm::MlKem768X25519 => "QSF-KEM(ML-KEM-768,X25519)-XOF(SHAKE256)-KDF(SHA3-256)",
+            Algorithm::MlKem1024P384 => "QSF-KEM(ML-KEM-1024,P-384)-XOF(SHAKE256)-KDF(SHA3-256)",
+        }
+    }
+
+    /// The runtime interface to the instance
+    pub fn kem(self) -> &'static dyn DynHybridKem {
+        match self {
+            Algorithm::MlKem768P256 => &Dyn::<MlKem768P256> {
+                algorithm: Algorithm::MlKem768P256,
+                _phantom: core::marker::PhantomData,
+            },
+            Algorithm::MlKem768X25519 => &Dyn::<MlKem768X25519> {
+                algorithm: Algorithm::MlKem768X25519,
+                _phantom: core::marker::PhantomData,
+            },
+            Algorithm::MlKem1024P384 => &Dyn::<MlKem1024P384> {
+                algorithm: Algorithm::MlKem1024P384,
+                _phantom: core::marker::PhantomData,
+            },
+        }
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    /// Parse either the draft name or the composition string of an instance
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Algorithm::ALL
+            .into_iter()
+            .find(|a| s == a.name() || s == a.composition())
+            .ok_or_else(|| Error::UnknownAlgorithm(s.to_string()))
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::kem::{EncapsDerand, Kem};
+
+    fn test_matches_static<K: HybridKem>(algorithm: Algorithm) {
+        let kem = algorithm.kem();
+        assert_eq!(kem.algorithm(), algorithm);
+        assert_eq!(kem.seed_size(), K::SEED_SIZE);
+        assert_eq!(kem.encapsulation_key_size(), K::ENCAPSULATION_KEY_SIZE);
+        assert_eq!(kem.decapsulation_key_size(), K::DECAPSULATION_KEY_SIZE);
+        assert_eq!(kem.ciphertext_size(), K::CIPHERTEXT_SIZE);
+        assert_eq!(kem.shared_secret_size(), K::SHARED_SECRET_SIZE);
+        assert_eq!(kem.randomness_size(), K::RANDOMNESS_SIZE);
+
+        let seed = vec![1u8; K::SEED_SIZE];
+        let randomness = vec![2u8; K::RANDOMNESS_SIZE];
+        let (dk, ek, _) = K::derive_key_pair(&seed);
+        let (ct, ss) = K::encaps_derand(&ek, &randomness);
+
+        assert_eq!(
+            kem.derive_key_pair(&seed).unwrap(),
+            (dk.clone(), ek.clone())
+        );
+        assert_eq!(
+            kem.encaps_derand(&ek, &randomness).unwrap(),
+            (ct.clone(), ss.clone())
+        );
+        assert_eq!(kem.decaps(&dk, &ct).unwrap(), ss);
+
+        let mut rng = rand::rng();
+        let (dk, ek) = kem.generate_key_pair(&mut rng);
+        let (ss, ct) = kem.encaps(&ek, &mut rng).unwrap();
+        assert_eq!(kem.decaps(&dk, &ct).unwrap(), ss);
+
+        assert_eq!(
+            kem.decaps(&dk, &ct[1..]),
+            Err(Error::InvalidLength {
+                field: "ciphertext",
+                expected: K::CIPHERTEXT_SIZE,
+                actual: K::CIPHERTEXT_SIZE - 1,
+            })
+        );
+        assert!(kem.derive_key_pair(&[]).is_err());
+        assert!(kem.encaps(&ek[1..], &mut rng).is_err());
+        assert!(kem.encaps_derand(&ek, &randomness[1..]).is_err());
+        assert!(kem.decaps(&dk[1..], &ct).is_err());
+    }
+
+    #[test]
+    fn dispatch() {
+        test_matches_static::<MlKem768P256>(Algorithm::MlKem768P256);
+        test_matches_static::<MlKem768X25519>(Algorithm::MlKem768X25519);
+        test_matches_static::<MlKem1024P384>(Algorithm::MlKem1024P384);
+    }
+
+    // Changing the last byte of the traditional component moves the point off the curve
+    #[test]
+    fn invalid_points() {
+        for algorithm in [Algorithm::MlKem768P256, Algorithm::MlKem1024P384] {
+            let kem = algorithm.kem();
+            let (dk, mut ek) = kem.derive_key_pair(&vec![1u8; kem.seed_size()]).unwrap();
+            let randomness = vec![2u8; kem.randomness_size()];
+            let (mut ct, _) = kem.encaps_derand(&ek, &randomness).unwrap();
+
+            *ct.last_mut().unwrap() ^= 0x01;
+            let invalid = Error::InvalidInput {
+                field: "ciphertext",
+            };
+            assert_eq!(kem.decaps(&dk, &ct), Err(invalid));
+
+            *ek.last_mut().unwrap() ^= 0x01;
+            let invalid = Error::InvalidInput {
+                field: "encapsulation key",
+            };
+            assert_eq!(kem.encaps_derand(&ek, &randomness), Err(invalid.clone()));
+            assert_eq!(kem.encaps(&ek, &mut rand::rng()), Err(invalid));
+        }
+    }
+
+    #[test]
+    fn names() {
+        for algorithm in Algorithm::ALL {
+            assert_eq!(algorithm.name().parse::<Algorithm>().unwrap(), algorithm);
+            assert_eq!(
+                algorithm.composition().parse::<Algorithm>().unwrap(),
+                algorithm
+            );
+            assert_eq!(algorithm.to_string(), algorithm.name());
+        }
+
+        assert_eq!(
+            "QSF-KEM(ML-KEM-768,P-256)-XOF(SHAKE256)-KDF(SHA3-256)".parse(),
+            Ok(Algorithm::MlKem768P256)
+        );
+        assert_eq!(
+            "QSF-KEM(ML-KEM-1024,P-384)-XOF(SHAKE256)-KDF(SHA3-256)".parse(),
+            Ok(Algorithm::MlKem1024P384)
+        );
+        assert_eq!(
+            "KitchenSink-KEM(ML-KEM-768,X25519)-XOF(SHAKE256)-KDF(HKDF-SHA-256)"
+                .parse::<Algorithm>(),
+            Err(Error::UnknownAlgorithm(
+                "KitchenSink-KEM(ML-KEM-768,X25519)-XOF(SHAKE256)-KDF(HKDF-SHA-256)".to_string()
+            ))
+        );
+        assert!("mlkem768-x25519".parse::<Algorithm>().is_err());
+    }
+
+    // The composition strings match the names of the checked-in spec files
+    #[test]
+    fn spec_file_names() {
+        let spec = concat!(env!("CARGO_MANIFEST_DIR"), "/../spec");
+        for algorithm in Algorithm::ALL {
+            let path = format!("{}/test-vectors-{}.txt", spec, algorithm.composition());
+            assert!(std::path::Path::new(&path).exists(), "missing {}", path);
+        }
+    }
+
+    // A server can hold a table of algorithms behind trait objects
+    #[test]
+    fn object_safe() {
+        let kems: Vec<&dyn DynHybridKem> = Algorithm::ALL.iter().map(|a| a.kem()).collect();
+        let sizes: Vec<usize> = kems.iter().map(|k| k.ciphertext_size()).collect();
+        assert_eq!(
+            sizes,
+            [
+                MlKem768P256::CIPHERTEXT_SIZE,
+                MlKem768X25519::CIPHERTEXT_SIZE,
+                MlKem1024P384::CIPHERTEXT_SIZE,
+            ]
+        );
+        assert_eq!(kems[1].randomness_size(), MlKem768X25519::RANDOMNESS_SIZE);
+    }
+}
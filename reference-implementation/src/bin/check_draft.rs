@@ -0,0 +1,53 @@
+//! Check the constants and labels in the draft against the code
+
+use concrete_hybrid_kem::draft;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 {
+        eprintln!("Usage: {} <draft.md>", args[0]);
+        process::exit(1);
+    }
+
+    let filename = &args[1];
+    let content = match fs::read_to_string(filename) {
+        Ok(content) => content,
+        Err(err) => {
+            eprintln!("Error reading file {}: {}", filename, err);
+            process::exit(1);
+        }
+    };
+
+    // Generated sections are included from files next to the draft
+    let dir = Path::new(filename).parent().unwrap_or(Path::new("."));
+    let content = match draft::expand_includes(&content, dir) {
+        Ok(content) => content,
+        Err(err) => {
+            eprintln!("Error reading included file: {}", err);
+            process::exit(1);
+        }
+    };
+
+    println!("Checking constants in {}...", filename);
+
+    let (allowed, discrepancies): (Vec<_>, Vec<_>) = draft::check(&content)
+        .into_iter()
+        .partition(draft::Discrepancy::is_allowed);
+    for discrepancy in &allowed {
+        println!("{} (allowed)", discrepancy);
+    }
+    for discrepancy in &discrepancies {
+        println!("{}", discrepancy);
+    }
+
+    if discrepancies.is_empty() {
+        println!("✅ The draft matches the code!");
+    } else {
+        println!("❌ Found {} discrepancies", discrepancies.len());
+        process::exit(1);
+    }
+}
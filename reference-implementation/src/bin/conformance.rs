@@ -0,0 +1,121 @@
+//! Randomized encapsulation conformance checks against other implementations
+
+use concrete_hybrid_kem::{
+    algorithm::Algorithm,
+    conformance::{self, EncapsulationCases},
+};
+use std::env;
+use std::fs;
+use std::process;
+
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} challenge [--count N] [--algorithm NAME]... > challenge.json",
+        program
+    );
+    eprintln!("       {} check <responses.json>", program);
+    eprintln!();
+    eprintln!("challenge: encapsulate with fresh randomness to the first N published key pairs");
+    eprintln!("           (default 10), for another implementation to decapsulate.");
+    eprintln!("check:     decapsulate ciphertexts produced by another implementation for the");
+    eprintln!("           published key pairs, and compare with the claimed shared secrets.");
+    process::exit(1);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        usage(&args[0]);
+    }
+
+    match args[1].as_str() {
+        "challenge" => challenge(&args),
+        "check" if args.len() == 3 => check(&args[2]),
+        _ => usage(&args[0]),
+    }
+}
+
+fn challenge(args: &[String]) {
+    let mut count: u8 = 10;
+    let mut algorithms = Vec::new();
+
+    let mut i = 2;
+    while i < args.len() {
+        let Some(value) = args.get(i + 1) else {
+            usage(&args[0]);
+        };
+
+        match args[i].as_str() {
+            "--count" => count = value.parse().unwrap_or_else(|_| usage(&args[0])),
+            "--algorithm" => match value.parse::<Algorithm>() {
+                Ok(algorithm) => algorithms.push(algorithm),
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                    process::exit(1);
+                }
+            },
+            _ => usage(&args[0]),
+        }
+        i += 2;
+    }
+
+    if algorithms.is_empty() {
+        algorithms = Algorithm::ALL.to_vec();
+    }
+
+    let mut rng = rand::rng();
+    let mut cases = EncapsulationCases::default();
+    for &algorithm in &algorithms {
+        *cases.get_mut(algorithm) = (0..count)
+            .map(|index| {
+                let seed = conformance::published_seed(algorithm, index);
+                conformance::challenge(algorithm, &seed, &mut rng).expect("Invalid seed length")
+            })
+            .collect();
+    }
+
+    println!("{}", serde_json::to_string_pretty(&cases).unwrap());
+}
+
+fn check(filename: &str) {
+    let content = match fs::read_to_string(filename) {
+        Ok(content) => content,
+        Err(err) => {
+            eprintln!("Error reading file {}: {}", filename, err);
+            process::exit(1);
+        }
+    };
+
+    let cases: EncapsulationCases = match serde_json::from_str(&content) {
+        Ok(cases) => cases,
+        Err(err) => {
+            eprintln!("Error parsing JSON: {}", err);
+            process::exit(1);
+        }
+    };
+
+    println!("Checking ciphertexts from {}...", filename);
+
+    let mut failures = 0;
+    for algorithm in Algorithm::ALL {
+        let cases = cases.get(algorithm);
+        if cases.is_empty() {
+            continue;
+        }
+
+        println!("Checking {} hybrid KEM...", algorithm);
+        for (i, case) in cases.iter().enumerate() {
+            if let Err(err) = conformance::check(algorithm, case) {
+                println!("Error in case #{} for {}: {}", i, algorithm, err);
+                failures += 1;
+            }
+        }
+    }
+
+    if failures == 0 {
+        println!("✅ All ciphertexts decapsulated to the claimed shared secrets!");
+    } else {
+        println!("❌ {} ciphertexts failed to check", failures);
+        process::exit(1);
+    }
+}
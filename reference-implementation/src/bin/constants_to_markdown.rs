@@ -0,0 +1,22 @@
+//! Markdown generator for the draft's instance sections and IANA table
+
+use concrete_hybrid_kem::{draft, registry};
+use std::env;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 {
+        eprintln!("Usage: {} <instances|iana>", args[0]);
+        process::exit(1);
+    }
+
+    match args[1].as_str() {
+        "instances" => print!("{}", draft::instance_sections()),
+        "iana" => print!("{}", registry::iana_table()),
+        _ => {
+            eprintln!("Usage: {} <instances|iana>", args[0]);
+            process::exit(1);
+        }
+    }
+}
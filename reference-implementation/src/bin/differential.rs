@@ -0,0 +1,141 @@
+//! Differential testing against another implementation over stdin/stdout
+
+use concrete_hybrid_kem::{
+    algorithm::Algorithm,
+    differential::{self, Error, Loopback, Peer, Subprocess},
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::env;
+use std::io::{self, BufRead, Write};
+use std::process;
+
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} run [--iterations N] [--algorithm NAME]... [--rng-seed N] (--loopback | -- <command> [args]...)",
+        program
+    );
+    eprintln!("       {} peer", program);
+    eprintln!();
+    eprintln!("run:  compare <command> with this implementation on random inputs (default 100");
+    eprintln!("      iterations for every instance), stopping at the first divergence.");
+    eprintln!("      --loopback tests against this implementation in-process instead.");
+    eprintln!("peer: answer requests on stdin with this implementation.");
+    process::exit(1);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        usage(&args[0]);
+    }
+
+    match args[1].as_str() {
+        "run" => run(&args),
+        "peer" if args.len() == 2 => peer(),
+        _ => usage(&args[0]),
+    }
+}
+
+fn run(args: &[String]) {
+    let mut iterations = 100;
+    let mut algorithms = Vec::new();
+    let mut rng_seed = None;
+    let mut loopback = false;
+    let mut command = None;
+
+    let mut i = 2;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--loopback" => {
+                loopback = true;
+                i += 1;
+                continue;
+            }
+            "--" if i + 1 < args.len() => {
+                command = Some(&args[i + 1..]);
+                break;
+            }
+            _ => {}
+        }
+
+        let Some(value) = args.get(i + 1) else {
+            usage(&args[0]);
+        };
+
+        match args[i].as_str() {
+            "--iterations" => iterations = value.parse().unwrap_or_else(|_| usage(&args[0])),
+            "--algorithm" => match value.parse::<Algorithm>() {
+                Ok(algorithm) => algorithms.push(algorithm),
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                    process::exit(1);
+                }
+            },
+            "--rng-seed" => {
+                rng_seed = Some(value.parse().unwrap_or_else(|_| usage(&args[0])));
+            }
+            _ => usage(&args[0]),
+        }
+        i += 2;
+    }
+
+    if algorithms.is_empty() {
+        algorithms = Algorithm::ALL.to_vec();
+    }
+
+    let mut peer: Box<dyn Peer> = match (loopback, command) {
+        (true, None) => Box::new(Loopback),
+        (false, Some(command)) => match Subprocess::spawn(&command[0], &command[1..]) {
+            Ok(peer) => Box::new(peer),
+            Err(err) => {
+                eprintln!("Error starting {}: {}", command[0], err);
+                process::exit(1);
+            }
+        },
+        _ => usage(&args[0]),
+    };
+
+    // Print the seed so that any run can be repeated
+    let rng_seed: u64 = rng_seed.unwrap_or_else(|| rand::rng().random());
+    let mut rng = StdRng::seed_from_u64(rng_seed);
+    println!(
+        "Running {} iterations for each instance (--rng-seed {})...",
+        iterations, rng_seed
+    );
+
+    match differential::run(peer.as_mut(), &algorithms, iterations, &mut rng) {
+        Ok(requests) => println!("✅ No divergence in {} requests", requests),
+        Err(Error::Divergence(divergence)) => {
+            println!("❌ Found a divergence");
+            println!("{}", divergence);
+            process::exit(1);
+        }
+        Err(err @ Error::Peer { .. }) => {
+            println!("❌ Error querying the peer");
+            println!("{}", err);
+            process::exit(1);
+        }
+    }
+}
+
+fn peer() {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                eprintln!("Error reading request: {}", err);
+                process::exit(1);
+            }
+        };
+
+        let response = differential::respond_line(&line);
+        if writeln!(stdout, "{}", response)
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            process::exit(1);
+        }
+    }
+}
@@ -1,34 +1,104 @@
 //! Test vector generation binary
 
 use concrete_hybrid_kem::{
-    test_vectors::{HybridKemTestVector, TestVectors},
-    MlKem1024P384, MlKem768P256, MlKem768X25519,
+    algorithm::Algorithm,
+    test_vectors::{self, Seeding, TestVectors},
 };
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} [--count N] [--algorithm NAME]... [--seeding index|shake128] [--format json|spec|markdown] [--output-dir DIR]",
+        program
+    );
+    eprintln!();
+    eprintln!("Defaults to 10 index-seeded vectors for every instance, as JSON.");
+    eprintln!("The spec format writes one file per instance to --output-dir, or a single");
+    eprintln!("--algorithm to stdout.");
+    process::exit(1);
+}
 
 fn main() {
-    eprintln!("Generating test vectors for concrete hybrid KEMs...");
+    let args: Vec<String> = env::args().collect();
+
+    let mut count = 10;
+    let mut algorithms = Vec::new();
+    let mut seeding = Seeding::Index;
+    let mut format = "json".to_string();
+    let mut output_dir = None;
 
-    // Generate 10 test vectors for each hybrid KEM
-    const N_VECTORS: u8 = 10;
+    let mut i = 1;
+    while i < args.len() {
+        let Some(value) = args.get(i + 1) else {
+            usage(&args[0]);
+        };
 
-    let mlkem768_p256 = (0..N_VECTORS)
-        .map(|i| HybridKemTestVector::generate::<MlKem768P256>(i))
-        .collect();
+        match args[i].as_str() {
+            "--count" => count = value.parse().unwrap_or_else(|_| usage(&args[0])),
+            "--algorithm" => match value.parse::<Algorithm>() {
+                Ok(algorithm) => algorithms.push(algorithm),
+                Err(err) => {
+                    eprintln!("Error: {}", err);
+                    process::exit(1);
+                }
+            },
+            "--seeding" => {
+                seeding = match value.as_str() {
+                    "index" => Seeding::Index,
+                    "shake128" => Seeding::Shake128,
+                    _ => usage(&args[0]),
+                }
+            }
+            "--format" => format = value.clone(),
+            "--output-dir" => output_dir = Some(PathBuf::from(value)),
+            _ => usage(&args[0]),
+        }
+        i += 2;
+    }
 
-    let mlkem768_x25519 = (0..N_VECTORS)
-        .map(|i| HybridKemTestVector::generate::<MlKem768X25519>(i))
-        .collect();
+    if algorithms.is_empty() {
+        algorithms = Algorithm::ALL.to_vec();
+    }
 
-    let mlkem1024_p384 = (0..N_VECTORS)
-        .map(|i| HybridKemTestVector::generate::<MlKem1024P384>(i))
-        .collect();
+    if output_dir.is_some() && format != "spec" {
+        usage(&args[0]);
+    }
+
+    if seeding == Seeding::Index && count > 256 {
+        eprintln!("Error: index seeding supports at most 256 vectors");
+        process::exit(1);
+    }
+
+    eprintln!("Generating test vectors for concrete hybrid KEMs...");
 
-    let test_vectors = TestVectors {
-        mlkem768_p256,
-        mlkem768_x25519,
-        mlkem1024_p384,
-    };
+    let test_vectors = TestVectors::generate(&algorithms, count, seeding);
 
-    // Output as JSON
-    println!("{}", serde_json::to_string_pretty(&test_vectors).unwrap());
+    match format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&test_vectors).unwrap()),
+        "markdown" => print!("{}", test_vectors.to_markdown()),
+        "spec" => match output_dir {
+            Some(dir) => {
+                for &algorithm in &algorithms {
+                    let path = dir.join(test_vectors::spec_filename(algorithm));
+                    let text = test_vectors::to_spec_text(test_vectors.get(algorithm));
+                    if let Err(err) = fs::write(&path, text) {
+                        eprintln!("Error writing file {}: {}", path.display(), err);
+                        process::exit(1);
+                    }
+                    eprintln!("Wrote {}", path.display());
+                }
+            }
+            None => {
+                if algorithms.len() != 1 {
+                    usage(&args[0]);
+                }
+                let vectors = test_vectors.get(algorithms[0]);
+                print!("{}", test_vectors::to_spec_text(vectors));
+            }
+        },
+        _ => usage(&args[0]),
+    }
 }
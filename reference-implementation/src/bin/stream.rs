@@ -0,0 +1,84 @@
+//! Streaming encryption to hybrid KEM recipients
+
+use concrete_hybrid_kem::stream::{self, Algorithm, Identity, Recipient};
+use std::env;
+use std::fs;
+use std::io;
+use std::process;
+
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} keygen <MLKEM768-X25519|MLKEM1024-P384|MLKEM768-P256>",
+        program
+    );
+    eprintln!(
+        "       {} encrypt <recipient>... < plaintext > ciphertext",
+        program
+    );
+    eprintln!(
+        "       {} decrypt <identity_file> < ciphertext > plaintext",
+        program
+    );
+    process::exit(1);
+}
+
+fn fail(err: impl std::fmt::Display) -> ! {
+    eprintln!("Error: {}", err);
+    process::exit(1);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 {
+        usage(&args[0]);
+    }
+
+    match args[1].as_str() {
+        "keygen" => {
+            let algorithm: Algorithm = args[2].parse().unwrap_or_else(|_| usage(&args[0]));
+            let identity = Identity::generate(algorithm, &mut rand::rng());
+            println!("# recipient: {}", identity.to_recipient());
+            println!("{}", identity);
+        }
+
+        "encrypt" => {
+            let recipients: Vec<Recipient> = args[2..]
+                .iter()
+                .map(|r| r.parse().unwrap_or_else(|err| fail(err)))
+                .collect();
+            let stdin = io::stdin().lock();
+            let stdout = io::stdout().lock();
+            if let Err(err) = stream::encrypt(&recipients, stdin, stdout, &mut rand::rng()) {
+                fail(err);
+            }
+        }
+
+        "decrypt" => {
+            if args.len() != 3 {
+                usage(&args[0]);
+            }
+
+            let filename = &args[2];
+            let content = match fs::read_to_string(filename) {
+                Ok(content) => content,
+                Err(err) => fail(format!("reading file {}: {}", filename, err)),
+            };
+
+            // Identity files may contain comments, as written by `keygen`
+            let identity: Identity = content
+                .lines()
+                .find(|line| !line.trim().is_empty() && !line.starts_with('#'))
+                .unwrap_or_default()
+                .parse()
+                .unwrap_or_else(|err| fail(err));
+
+            let stdin = io::stdin().lock();
+            let stdout = io::stdout().lock();
+            if let Err(err) = stream::decrypt(&identity, stdin, stdout) {
+                fail(err);
+            }
+        }
+
+        _ => usage(&args[0]),
+    }
+}
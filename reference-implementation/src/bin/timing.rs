@@ -0,0 +1,88 @@
+//! Statistical tests for secret-dependent timing in decapsulation and the group operations
+
+use concrete_hybrid_kem::timing::{DEFAULT_THRESHOLD, TARGETS};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::env;
+use std::process;
+
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} [--measurements N] [--threshold T] [--target NAME]... [--rng-seed N]",
+        program
+    );
+    eprintln!();
+    eprintln!("Time each target on fixed and random secret inputs (default 20000 measurements),");
+    eprintln!(
+        "and fail if Welch's |t| exceeds the threshold (default {}).",
+        DEFAULT_THRESHOLD
+    );
+    eprintln!("--target selects the targets whose names contain NAME. Targets:");
+    for target in TARGETS {
+        eprintln!("  {}", target.name);
+    }
+    eprintln!("Build with --release; debug builds are too noisy to be meaningful.");
+    process::exit(1);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut measurements = 20_000;
+    let mut threshold = DEFAULT_THRESHOLD;
+    let mut filters = Vec::new();
+    let mut rng_seed = None;
+
+    let mut i = 1;
+    while i < args.len() {
+        let Some(value) = args.get(i + 1) else {
+            usage(&args[0]);
+        };
+
+        match args[i].as_str() {
+            "--measurements" => {
+                measurements = value.parse().unwrap_or_else(|_| usage(&args[0]));
+            }
+            "--threshold" => threshold = value.parse().unwrap_or_else(|_| usage(&args[0])),
+            "--target" => filters.push(value.clone()),
+            "--rng-seed" => {
+                rng_seed = Some(value.parse().unwrap_or_else(|_| usage(&args[0])));
+            }
+            _ => usage(&args[0]),
+        }
+        i += 2;
+    }
+
+    let targets: Vec<_> = TARGETS
+        .iter()
+        .filter(|target| filters.is_empty() || filters.iter().any(|f| target.name.contains(f)))
+        .collect();
+    if targets.is_empty() {
+        eprintln!("Error: no target matches {:?}", filters);
+        process::exit(1);
+    }
+
+    // Print the seed so that any run can be repeated
+    let rng_seed: u64 = rng_seed.unwrap_or_else(|| rand::rng().random());
+    let mut rng = StdRng::seed_from_u64(rng_seed);
+    println!(
+        "Timing {} targets, {} measurements each, threshold |t| <= {} (--rng-seed {})...",
+        targets.len(),
+        measurements,
+        threshold,
+        rng_seed
+    );
+
+    let mut failures = 0;
+    for target in targets {
+        let outcome = target.run(measurements, threshold, &mut rng);
+        println!("{}", outcome);
+        if !outcome.passed() {
+            failures += 1;
+        }
+    }
+
+    if failures > 0 {
+        println!("❌ {} targets show timing differences", failures);
+        process::exit(1);
+    }
+    println!("✅ No timing differences found");
+}
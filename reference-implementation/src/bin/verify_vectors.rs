@@ -1,22 +1,84 @@
 //! Test vector verification binary
 
 use concrete_hybrid_kem::{
-    hybrid::HybridKem,
-    test_vectors::{HybridKemTestVector, TestVectors, VerifyError},
-    MlKem1024P384, MlKem768P256, MlKem768X25519,
+    algorithm::Algorithm,
+    test_vectors::{TestVectors, VerifyError},
 };
+use serde::Serialize;
 use std::env;
 use std::fs;
 use std::process;
 
+/// Machine-readable verification results, as printed by `--format json`
+#[derive(Serialize)]
+struct Report {
+    file: String,
+    ok: bool,
+    instances: Vec<InstanceReport>,
+}
+
+#[derive(Serialize)]
+struct InstanceReport {
+    name: &'static str,
+    ok: bool,
+    vectors: Vec<VectorReport>,
+}
+
+#[derive(Serialize)]
+struct VectorReport {
+    index: usize,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    failure: Option<Failure>,
+}
+
+/// The values and offset are only present for mismatches; an input of the wrong length is only
+/// described by `message`
+#[derive(Serialize)]
+struct Failure {
+    field: &'static str,
+    operation: &'static str,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expected: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    actual: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_difference: Option<usize>,
+}
+
+impl From<&VerifyError> for Failure {
+    fn from(err: &VerifyError) -> Self {
+        Failure {
+            field: err.field(),
+            operation: err.operation(),
+            message: err.to_string(),
+            expected: err.expected().map(hex::encode),
+            actual: err.actual().map(hex::encode),
+            first_difference: err.first_difference(),
+        }
+    }
+}
+
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} [--format text|json] <test_vectors.json>",
+        program
+    );
+    process::exit(1);
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Usage: {} <test_vectors.json>", args[0]);
-        process::exit(1);
+    let (format, filename) = match &args[1..] {
+        [filename] => ("text", filename),
+        [flag, format, filename] if flag == "--format" => (format.as_str(), filename),
+        _ => usage(&args[0]),
+    };
+    if format != "text" && format != "json" {
+        usage(&args[0]);
     }
 
-    let filename = &args[1];
     let content = match fs::read_to_string(filename) {
         Ok(content) => content,
         Err(err) => {
@@ -33,70 +95,77 @@ fn main() {
         }
     };
 
-    println!("Verifying test vectors from {}...", filename);
-
-    let mlkem768_p256 =
-        verify_hybrid_kem_vectors::<MlKem768P256>("MLKEM768-P256", &test_vectors.mlkem768_p256);
-
-    let mlkem768_x25519 = verify_hybrid_kem_vectors::<MlKem768X25519>(
-        "MLKEM768-X25519",
-        &test_vectors.mlkem768_x25519,
-    );
-
-    let mlkem1024_p384 =
-        verify_hybrid_kem_vectors::<MlKem1024P384>("MLKEM1024-P384", &test_vectors.mlkem1024_p384);
+    let report = verify(filename, &test_vectors);
 
-    if mlkem768_p256 && mlkem768_x25519 && mlkem1024_p384 {
-        println!("✅ All test vectors verified successfully!");
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
     } else {
-        println!("❌ Some test vectors failed verification");
+        print_text(&report);
+    }
+
+    if !report.ok {
         process::exit(1);
     }
 }
 
-fn verify_hybrid_kem_vectors<K: HybridKem>(name: &str, vectors: &[HybridKemTestVector]) -> bool {
-    println!("Verifying {} hybrid KEM...", name);
-
-    let error_count = vectors
-        .iter()
-        .map(|v| v.verify::<K>())
-        .enumerate()
-        .filter_map(|(i, rv)| rv.err().map(|rv| (i, rv)))
-        .map(|(i, err)| print_failure(name, i, err))
-        .count();
-
-    error_count == 0
+fn verify(filename: &str, test_vectors: &TestVectors) -> Report {
+    let instances: Vec<InstanceReport> = Algorithm::ALL
+        .into_iter()
+        .filter(|&algorithm| !test_vectors.get(algorithm).is_empty())
+        .map(|algorithm| {
+            let vectors: Vec<VectorReport> = test_vectors
+                .verify(algorithm)
+                .iter()
+                .enumerate()
+                .map(|(index, result)| VectorReport {
+                    index,
+                    ok: result.is_ok(),
+                    failure: result.as_ref().err().map(Failure::from),
+                })
+                .collect();
+
+            InstanceReport {
+                name: algorithm.name(),
+                ok: vectors.iter().all(|v| v.ok),
+                vectors,
+            }
+        })
+        .collect();
+
+    Report {
+        file: filename.to_string(),
+        ok: instances.iter().all(|i| i.ok),
+        instances,
+    }
 }
 
-fn print_failure(name: &str, index: usize, err: VerifyError) {
-    println!("Error in vector #{} for {}", index, name);
-    match err {
-        VerifyError::EncapsulationKey(my, vec) => println!(
-            "EncapsulationKey my={} vec={}",
-            hex::encode(my),
-            hex::encode(vec)
-        ),
-
-        VerifyError::DecapsulationKey(my, vec) => println!(
-            "DecapsulationKey my={} vec={}",
-            hex::encode(my),
-            hex::encode(vec)
-        ),
-
-        VerifyError::Ciphertext(my, vec) => {
-            println!("Ciphertext my={} vec={}", hex::encode(my), hex::encode(vec))
+fn print_text(report: &Report) {
+    println!("Verifying test vectors from {}...", report.file);
+
+    for instance in &report.instances {
+        println!("Verifying {} hybrid KEM...", instance.name);
+        for vector in &instance.vectors {
+            if let Some(failure) = &vector.failure {
+                println!("Error in vector #{} for {}", vector.index, instance.name);
+                let (Some(expected), Some(actual), Some(offset)) =
+                    (&failure.expected, &failure.actual, failure.first_difference)
+                else {
+                    println!("{}", failure.message);
+                    continue;
+                };
+                println!(
+                    "{} from {} differs at byte {}",
+                    failure.field, failure.operation, offset
+                );
+                println!("expected={}", expected);
+                println!("actual={}", actual);
+            }
         }
+    }
 
-        VerifyError::SharedSecretEncaps(my, vec) => println!(
-            "SharedSecretEncaps my={} vec={}",
-            hex::encode(my),
-            hex::encode(vec)
-        ),
-
-        VerifyError::SharedSecretDecaps(my, vec) => println!(
-            "SharedSecretDecaps my={} vec={}",
-            hex::encode(my),
-            hex::encode(vec)
-        ),
+    if report.ok {
+        println!("✅ All test vectors verified successfully!");
+    } else {
+        println!("❌ Some test vectors failed verification");
     }
 }
@@ -0,0 +1,219 @@
+//! Conformance checks for randomized encapsulation
+//!
+//! Test vectors only cover `encaps_derand`.  To test randomized `encaps` across implementations,
+//! both sides exchange encapsulation cases for the published key pairs, identified by their seed
+//! (the seeds of `test-vectors.json` by default):
+//!
+//! * `challenge` produces fresh ciphertexts with this implementation, for another implementation
+//!   to decapsulate with the key pair derived from `seed`.
+//! * `check` decapsulates ciphertexts produced by another implementation and compares the result
+//!   with the shared secret it claims.
+
+use crate::algorithm::Algorithm;
+use rand::CryptoRng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A ciphertext for the key pair derived from `seed`, and the shared secret it encapsulates
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncapsulationCase {
+    #[serde(with = "hex::serde")]
+    pub seed: Vec<u8>,
+
+    #[serde(with = "hex::serde")]
+    pub encapsulation_key: Vec<u8>,
+
+    #[serde(with = "hex::serde")]
+    pub ciphertext: Vec<u8>,
+
+    #[serde(with = "hex::serde")]
+    pub shared_secret: Vec<u8>,
+}
+
+/// Encapsulation cases for each hybrid KEM instance, laid out like `TestVectors`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EncapsulationCases {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mlkem768_p256: Vec<EncapsulationCase>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mlkem768_x25519: Vec<EncapsulationCase>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mlkem1024_p384: Vec<EncapsulationCase>,
+}
+
+impl EncapsulationCases {
+    pub fn get(&self, algorithm: Algorithm) -> &Vec<EncapsulationCase> {
+        match algorithm {
+            Algorithm::MlKem768P256 => &self.mlkem768_p256,
+            Algorithm::MlKem768X25519 => &self.mlkem768_x25519,
+            Algorithm::MlKem1024P384 => &self.mlkem1024_p384,
+        }
+    }
+
+    pub fn get_mut(&mut self, algorithm: Algorithm) -> &mut Vec<EncapsulationCase> {
+        match algorithm {
+            Algorithm::MlKem768P256 => &mut self.mlkem768_p256,
+            Algorithm::MlKem768X25519 => &mut self.mlkem768_x25519,
+            Algorithm::MlKem1024P384 => &mut self.mlkem1024_p384,
+        }
+    }
+}
+
+/// An enumeration of the ways an encapsulation case can fail to check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CheckError {
+    /// A field has the wrong length for the instance, or is not a valid value (e.g., the
+    /// ciphertext holds an invalid curve point)
+    Input(crate::algorithm::Error),
+    /// The encapsulation key differs from the one derived from the seed
+    EncapsulationKey,
+    /// Decapsulation produced a different shared secret than the one claimed
+    SharedSecret { expected: Vec<u8>, actual: Vec<u8> },
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckError::Input(err) => write!(f, "{}", err),
+            CheckError::EncapsulationKey => {
+                write!(f, "encapsulation key does not match the seed")
+            }
+            CheckError::SharedSecret { expected, actual } => write!(
+                f,
+                "shared secret mismatch: expected {}, got {}",
+                hex::encode(expected),
+                hex::encode(actual)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CheckError {}
+
+impl From<crate::algorithm::Error> for CheckError {
+    fn from(err: crate::algorithm::Error) -> Self {
+        CheckError::Input(err)
+    }
+}
+
+/// The key generation seed of the `index`-th published key pair, as in `test-vectors.json`
+pub fn published_seed(algorithm: Algorithm, index: u8) -> Vec<u8> {
+    vec![index; algorithm.kem().seed_size()]
+}
+
+/// Encapsulate to the key pair derived from `seed` with fresh randomness
+pub fn challenge(
+    algorithm: Algorithm,
+    seed: &[u8],
+    rng: &mut dyn CryptoRng,
+) -> Result<EncapsulationCase, CheckError> {
+    let kem = algorithm.kem();
+    let (_, ek) = kem.derive_key_pair(seed)?;
+    let (ss, ct) = kem.encaps(&ek, rng)?;
+    Ok(EncapsulationCase {
+        seed: seed.to_vec(),
+        encapsulation_key: ek,
+        ciphertext: ct,
+        shared_secret: ss,
+    })
+}
+
+/// Check that decapsulating `case.ciphertext` yields `case.shared_secret`
+pub fn check(algorithm: Algorithm, case: &EncapsulationCase) -> Result<(), CheckError> {
+    let kem = algorithm.kem();
+    let (dk, ek) = kem.derive_key_pair(&case.seed)?;
+    if ek != case.encapsulation_key {
+        return Err(CheckError::EncapsulationKey);
+    }
+
+    let ss = kem.decaps(&dk, &case.ciphertext)?;
+    if ss != case.shared_secret {
+        return Err(CheckError::SharedSecret {
+            expected: case.shared_secret.clone(),
+            actual: ss,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MlKem768P256;
+    use crate::hybrid::test::off_curve_ciphertext;
+
+    #[test]
+    fn challenge_and_check() {
+        let mut rng = rand::rng();
+        for algorithm in Algorithm::ALL {
+            let seed = published_seed(algorithm, 3);
+            let case = challenge(algorithm, &seed, &mut rng).unwrap();
+            assert_eq!(check(algorithm, &case), Ok(()));
+
+            // Encapsulation is randomized
+            let other = challenge(algorithm, &seed, &mut rng).unwrap();
+            assert_eq!(other.encapsulation_key, case.encapsulation_key);
+            assert_ne!(other.ciphertext, case.ciphertext);
+
+            let mut bad = case.clone();
+            bad.shared_secret[0] ^= 1;
+            assert!(matches!(
+                check(algorithm, &bad),
+                Err(CheckError::SharedSecret { .. })
+            ));
+
+            let mut bad = case.clone();
+            bad.seed = published_seed(algorithm, 4);
+            assert_eq!(check(algorithm, &bad), Err(CheckError::EncapsulationKey));
+
+            let mut bad = case.clone();
+            bad.ciphertext.pop();
+            assert!(matches!(check(algorithm, &bad), Err(CheckError::Input(_))));
+        }
+    }
+
+    // A ciphertext that the runtime interface rejects is reported as an input error
+    #[test]
+    fn invalid_point() {
+        let algorithm = Algorithm::MlKem768P256;
+        let mut case =
+            challenge(algorithm, &published_seed(algorithm, 0), &mut rand::rng()).unwrap();
+        case.ciphertext = off_curve_ciphertext::<MlKem768P256>(&case.encapsulation_key);
+        assert_eq!(
+            check(algorithm, &case),
+            Err(CheckError::Input(crate::algorithm::Error::InvalidInput {
+                field: "ciphertext"
+            }))
+        );
+    }
+
+    // The published key pairs are those of the checked-in test vectors
+    #[test]
+    fn published_keys() {
+        use crate::test_vectors::TestVectors;
+
+        let json = include_str!("../../test-vectors.json");
+        let test_vectors: TestVectors = serde_json::from_str(json).unwrap();
+        for algorithm in Algorithm::ALL {
+            for (i, v) in test_vectors.get(algorithm).iter().enumerate() {
+                assert_eq!(v.seed, published_seed(algorithm, i as u8));
+            }
+        }
+    }
+
+    #[test]
+    fn serialization() {
+        let mut rng = rand::rng();
+        let mut cases = EncapsulationCases::default();
+        let algorithm = Algorithm::MlKem768X25519;
+        let case = challenge(algorithm, &published_seed(algorithm, 0), &mut rng).unwrap();
+        cases.get_mut(algorithm).push(case);
+
+        let json = serde_json::to_string(&cases).unwrap();
+        assert!(json.starts_with("{\"mlkem768_x25519\":[{\"seed\":\"0000"));
+        let parsed: EncapsulationCases = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.get(algorithm), cases.get(algorithm));
+        assert!(parsed.get(Algorithm::MlKem768P256).is_empty());
+    }
+}
@@ -0,0 +1,454 @@
+//! Differential testing against other implementations
+//!
+//! The harness drives a peer implementation over a line-oriented JSON protocol: each request
+//! is one JSON object on a line, naming the operation and the instance,
+//!
+//! ~~~text
+//! {"op":"keygen","algorithm":"MLKEM768-X25519","seed":"..."}
+//! {"op":"encaps_derand","algorithm":"MLKEM768-X25519","ek":"...","randomness":"..."}
+//! {"op":"decaps","algorithm":"MLKEM768-X25519","dk":"...","ct":"..."}
+//! ~~~
+//!
+//! and the peer answers each on a line with `{"ek":...,"dk":...}`, `{"ct":...,"ss":...}` or
+//! `{"ss":...}` respectively, or with `{"error":"..."}` if it rejects the request.  Byte strings
+//! are hex encoded, and instances are named as in `Algorithm::name`.
+//!
+//! Requests are built from this implementation's outputs only, so the first request with a
+//! different response is a reproducer on its own.
+
+use crate::algorithm::{self, Algorithm};
+use rand::CryptoRng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+/// A request to the peer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Request {
+    Keygen {
+        algorithm: String,
+        #[serde(with = "hex::serde")]
+        seed: Vec<u8>,
+    },
+    EncapsDerand {
+        algorithm: String,
+        #[serde(with = "hex::serde")]
+        ek: Vec<u8>,
+        #[serde(with = "hex::serde")]
+        randomness: Vec<u8>,
+    },
+    Decaps {
+        algorithm: String,
+        #[serde(with = "hex::serde")]
+        dk: Vec<u8>,
+        #[serde(with = "hex::serde")]
+        ct: Vec<u8>,
+    },
+}
+
+impl Request {
+    /// The name of the instance the request is for
+    pub fn algorithm(&self) -> &str {
+        match self {
+            Request::Keygen { algorithm, .. }
+            | Request::EncapsDerand { algorithm, .. }
+            | Request::Decaps { algorithm, .. } => algorithm,
+        }
+    }
+
+    /// The name of the operation, as in the `op` field
+    pub fn operation(&self) -> &'static str {
+        match self {
+            Request::Keygen { .. } => "keygen",
+            Request::EncapsDerand { .. } => "encaps_derand",
+            Request::Decaps { .. } => "decaps",
+        }
+    }
+}
+
+/// A response from the peer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Response {
+    KeyPair {
+        #[serde(with = "hex::serde")]
+        ek: Vec<u8>,
+        #[serde(with = "hex::serde")]
+        dk: Vec<u8>,
+    },
+    // Must come before `SharedSecret`, which would also match
+    Encapsulation {
+        #[serde(with = "hex::serde")]
+        ct: Vec<u8>,
+        #[serde(with = "hex::serde")]
+        ss: Vec<u8>,
+    },
+    SharedSecret {
+        #[serde(with = "hex::serde")]
+        ss: Vec<u8>,
+    },
+    Error {
+        error: String,
+    },
+}
+
+/// Answer a request with this implementation.  Requests for unknown instances, and inputs of the
+/// wrong length or with invalid curve points, are rejected with an error.
+pub fn respond(request: &Request) -> Result<Response, algorithm::Error> {
+    let kem = request.algorithm().parse::<Algorithm>()?.kem();
+    match request {
+        Request::Keygen { seed, .. } => kem
+            .derive_key_pair(seed)
+            .map(|(dk, ek)| Response::KeyPair { ek, dk }),
+        Request::EncapsDerand { ek, randomness, .. } => kem
+            .encaps_derand(ek, randomness)
+            .map(|(ct, ss)| Response::Encapsulation { ct, ss }),
+        Request::Decaps { dk, ct, .. } => {
+            kem.decaps(dk, ct).map(|ss| Response::SharedSecret { ss })
+        }
+    }
+}
+
+/// Answer a request line with a response line, without the trailing newline
+pub fn respond_line(line: &str) -> String {
+    let response = match serde_json::from_str(line) {
+        Ok(request) => respond(&request).unwrap_or_else(|err| Response::Error {
+            error: err.to_string(),
+        }),
+        Err(err) => Response::Error {
+            error: format!("invalid request: {}", err),
+        },
+    };
+    serde_json::to_string(&response).unwrap()
+}
+
+/// An implementation under test
+pub trait Peer {
+    fn query(&mut self, request: &Request) -> io::Result<Response>;
+}
+
+/// A peer backed by this implementation, speaking the protocol in-process
+pub struct Loopback;
+
+impl Peer for Loopback {
+    fn query(&mut self, request: &Request) -> io::Result<Response> {
+        let line = respond_line(&serde_json::to_string(request)?);
+        Ok(serde_json::from_str(&line)?)
+    }
+}
+
+/// A peer running as a child process, reading requests on stdin and answering on stdout
+pub struct Subprocess {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Subprocess {
+    pub fn spawn(program: &str, args: &[String]) -> io::Result<Self> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take();
+        let stdout = BufReader::new(child.stdout.take().unwrap());
+        Ok(Self {
+            child,
+            stdin,
+            stdout,
+        })
+    }
+}
+
+impl Peer for Subprocess {
+    fn query(&mut self, request: &Request) -> io::Result<Response> {
+        let stdin = self.stdin.as_mut().unwrap();
+        writeln!(stdin, "{}", serde_json::to_string(request)?)?;
+        stdin.flush()?;
+
+        let mut line = String::new();
+        if self.stdout.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "peer closed its output",
+            ));
+        }
+        Ok(serde_json::from_str(&line)?)
+    }
+}
+
+impl Drop for Subprocess {
+    fn drop(&mut self) {
+        // Closing stdin tells the peer to exit
+        self.stdin.take();
+        let _ = self.child.wait();
+    }
+}
+
+/// A request on which the peer disagrees with this implementation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    pub request: Request,
+    pub expected: Response,
+    pub actual: Response,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} diverged for {}",
+            self.request.operation(),
+            self.request.algorithm()
+        )?;
+        writeln!(
+            f,
+            "request:  {}",
+            serde_json::to_string(&self.request).unwrap()
+        )?;
+        writeln!(
+            f,
+            "expected: {}",
+            serde_json::to_string(&self.expected).unwrap()
+        )?;
+        write!(
+            f,
+            "actual:   {}",
+            serde_json::to_string(&self.actual).unwrap()
+        )
+    }
+}
+
+/// An enumeration of the ways a differential run can stop
+#[derive(Debug)]
+pub enum Error {
+    /// The peer could not be queried, or its response could not be parsed
+    Peer { request: Request, err: io::Error },
+    /// The peer's response differs from this implementation's
+    Divergence(Box<Divergence>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Peer { request, err } => write!(
+                f,
+                "{} for {} failed: {}",
+                request.operation(),
+                request.algorithm(),
+                err
+            ),
+            Error::Divergence(divergence) => write!(f, "{}", divergence),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn exchange(peer: &mut dyn Peer, request: Request, expected: Response) -> Result<(), Error> {
+    let actual = match peer.query(&request) {
+        Ok(actual) => actual,
+        Err(err) => return Err(Error::Peer { request, err }),
+    };
+
+    if actual != expected {
+        return Err(Error::Divergence(Box::new(Divergence {
+            request,
+            expected,
+            actual,
+        })));
+    }
+
+    Ok(())
+}
+
+fn random_bytes(rng: &mut dyn CryptoRng, len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    rng.fill_bytes(&mut bytes);
+    bytes
+}
+
+/// Compare the peer with this implementation on random inputs, `iterations` times for each of
+/// `algorithms`, stopping at the first divergence.  Returns the number of requests made.
+///
+/// Each iteration derives a key pair from a random seed, encapsulates to it with random
+/// randomness, and decapsulates both the resulting ciphertext and a copy with a flipped bit in
+/// the PQ ciphertext, to exercise implicit rejection.
+pub fn run(
+    peer: &mut dyn Peer,
+    algorithms: &[Algorithm],
+    iterations: usize,
+    rng: &mut dyn CryptoRng,
+) -> Result<usize, Error> {
+    let mut requests = 0;
+    for _ in 0..iterations {
+        for &algorithm in algorithms {
+            let kem = algorithm.kem();
+            let name = algorithm.name().to_string();
+
+            let seed = random_bytes(rng, kem.seed_size());
+            let (dk, ek) = kem.derive_key_pair(&seed).unwrap();
+            let request = Request::Keygen {
+                algorithm: name.clone(),
+                seed,
+            };
+            let expected = Response::KeyPair {
+                ek: ek.clone(),
+                dk: dk.clone(),
+            };
+            exchange(peer, request, expected)?;
+
+            let randomness = random_bytes(rng, kem.randomness_size());
+            let (ct, ss) = kem.encaps_derand(&ek, &randomness).unwrap();
+            let request = Request::EncapsDerand {
+                algorithm: name.clone(),
+                ek,
+                randomness,
+            };
+            let expected = Response::Encapsulation {
+                ct: ct.clone(),
+                ss: ss.clone(),
+            };
+            exchange(peer, request, expected)?;
+
+            let request = Request::Decaps {
+                algorithm: name.clone(),
+                dk: dk.clone(),
+                ct: ct.clone(),
+            };
+            exchange(peer, request, Response::SharedSecret { ss })?;
+
+            // The PQ ciphertext comes first
+            let mut ct = ct;
+            ct[0] ^= 1;
+            let ss = kem.decaps(&dk, &ct).unwrap();
+            let request = Request::Decaps {
+                algorithm: name,
+                dk,
+                ct,
+            };
+            exchange(peer, request, Response::SharedSecret { ss })?;
+
+            requests += 4;
+        }
+    }
+
+    Ok(requests)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MlKem768P256;
+    use crate::hybrid::test::off_curve_ciphertext;
+    use crate::kem::Kem;
+
+    #[test]
+    fn loopback() {
+        let mut rng = rand::rng();
+        let requests = run(&mut Loopback, &Algorithm::ALL, 2, &mut rng).unwrap();
+        assert_eq!(requests, 2 * Algorithm::ALL.len() * 4);
+    }
+
+    #[test]
+    fn protocol() {
+        let request = Request::Keygen {
+            algorithm: "MLKEM768-X25519".to_string(),
+            seed: vec![0; 32],
+        };
+        let line = serde_json::to_string(&request).unwrap();
+        assert_eq!(
+            line,
+            format!(
+                "{{\"op\":\"keygen\",\"algorithm\":\"MLKEM768-X25519\",\"seed\":\"{}\"}}",
+                "00".repeat(32)
+            )
+        );
+
+        let response: Response = serde_json::from_str(&respond_line(&line)).unwrap();
+        let (dk, ek) = Algorithm::MlKem768X25519
+            .kem()
+            .derive_key_pair(&[0; 32])
+            .unwrap();
+        assert_eq!(response, Response::KeyPair { ek, dk });
+
+        let response: Response = serde_json::from_str(r#"{"ct":"00","ss":"01"}"#).unwrap();
+        assert_eq!(
+            response,
+            Response::Encapsulation {
+                ct: vec![0],
+                ss: vec![1]
+            }
+        );
+
+        let response: Response = serde_json::from_str(&respond_line("{}")).unwrap();
+        assert!(matches!(response, Response::Error { .. }));
+
+        let line = r#"{"op":"decaps","algorithm":"MLKEM768-P256","dk":"00","ct":"00"}"#;
+        let response: Response = serde_json::from_str(&respond_line(line)).unwrap();
+        assert!(matches!(response, Response::Error { .. }));
+
+        let line = r#"{"op":"keygen","algorithm":"X-Wing","seed":"00"}"#;
+        let response: Response = serde_json::from_str(&respond_line(line)).unwrap();
+        assert!(matches!(response, Response::Error { .. }));
+    }
+
+    // Invalid inputs are reported as the runtime interface's error
+    #[test]
+    fn invalid_point() {
+        let (dk, ek, _) = MlKem768P256::derive_key_pair(&[0; 32]);
+        let ct = off_curve_ciphertext::<MlKem768P256>(&ek);
+
+        let request = Request::Decaps {
+            algorithm: "MLKEM768-P256".to_string(),
+            dk,
+            ct,
+        };
+        assert_eq!(
+            respond(&request),
+            Err(algorithm::Error::InvalidInput {
+                field: "ciphertext"
+            })
+        );
+        let line = respond_line(&serde_json::to_string(&request).unwrap());
+        assert_eq!(line, r#"{"error":"ciphertext: invalid value"}"#);
+    }
+
+    // A peer that gets implicit rejection wrong for one instance
+    struct Faulty(Algorithm);
+
+    impl Peer for Faulty {
+        fn query(&mut self, request: &Request) -> io::Result<Response> {
+            let mut response = Loopback.query(request)?;
+            if let (Request::Decaps { algorithm, ct, .. }, Response::SharedSecret { ss }) =
+                (request, &mut response)
+                && *algorithm == self.0.name()
+                && ct[0] & 1 == 1
+            {
+                ss[0] ^= 1;
+            }
+            Ok(response)
+        }
+    }
+
+    #[test]
+    fn divergence() {
+        let mut rng = rand::rng();
+        let mut peer = Faulty(Algorithm::MlKem1024P384);
+        let err = run(&mut peer, &Algorithm::ALL, 10, &mut rng).unwrap_err();
+        let Error::Divergence(divergence) = err else {
+            panic!("unexpected error: {}", err);
+        };
+
+        assert_eq!(divergence.request.operation(), "decaps");
+        assert_eq!(divergence.request.algorithm(), "MLKEM1024-P384");
+        assert_ne!(divergence.expected, divergence.actual);
+
+        // The reproducer stands on its own
+        assert_eq!(respond(&divergence.request), Ok(divergence.expected));
+        assert_eq!(peer.query(&divergence.request).unwrap(), divergence.actual);
+    }
+}
@@ -0,0 +1,472 @@
+//! Consistency checks between the draft and the code
+//!
+//! The draft lists the constants of each nominal group, component KEM, and hybrid KEM as bullet
+//! lists of the form "- `Nek`: 1216", and the label of each hybrid KEM as
+//! "* `Label`: `...` (0x...)".  `check` extracts these and compares them with the constants of the
+//! corresponding Rust types.
+
+use crate::algorithm::Algorithm;
+use crate::group::{self, NominalGroup};
+use crate::kem::{self, Kem};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The constants whose values are checked
+const CHECKED: [&str; 7] = ["Nseed", "Nek", "Ndk", "Nct", "Nss", "Nelem", "Nscalar"];
+
+/// Known differences between the draft and the code, as (subject, constant, draft, code).  Each
+/// entry must say why the difference is acceptable; the draft and code values are part of the
+/// entry, so any further change to either is reported again.
+pub const ALLOWED: [(&str, &str, usize, usize); 1] = [
+    // The draft's P-256 RandomScalar rejection samples from up to four 32-byte candidates taken
+    // from the seed, where the code expands a 32-byte seed with SHAKE256 and rejection samples
+    // from its output (see `group::P256`)
+    ("P-256", "Nseed", 128, 32),
+];
+
+/// A group, KEM, or hybrid KEM described in the draft, with its constants in the code
+struct Subject {
+    name: &'static str,
+    constants: Vec<(&'static str, usize)>,
+    label: Option<&'static [u8]>,
+}
+
+fn group<G: NominalGroup>(name: &'static str) -> Subject {
+    Subject {
+        name,
+        constants: vec![
+            ("Nseed", G::SEED_SIZE),
+            ("Nscalar", G::SCALAR_SIZE),
+            ("Nelem", G::ELEMENT_SIZE),
+            ("Nss", G::SHARED_SECRET_SIZE),
+        ],
+        label: None,
+    }
+}
+
+fn kem<K: Kem>(name: &'static str) -> Subject {
+    Subject {
+        name,
+        constants: vec![
+            ("Nseed", K::SEED_SIZE),
+            ("Nek", K::ENCAPSULATION_KEY_SIZE),
+            ("Ndk", K::DECAPSULATION_KEY_SIZE),
+            ("Nct", K::CIPHERTEXT_SIZE),
+            ("Nss", K::SHARED_SECRET_SIZE),
+        ],
+        label: None,
+    }
+}
+
+fn hybrid(algorithm: Algorithm) -> Subject {
+    let kem = algorithm.kem();
+    Subject {
+        name: algorithm.name(),
+        constants: vec![
+            ("Nseed", kem.seed_size()),
+            ("Nek", kem.encapsulation_key_size()),
+            ("Ndk", kem.decapsulation_key_size()),
+            ("Nct", kem.ciphertext_size()),
+            ("Nss", kem.shared_secret_size()),
+        ],
+        label: Some(algorithm.registry_entry().label),
+    }
+}
+
+fn subjects() -> Vec<Subject> {
+    let mut subjects = vec![
+        group::<group::P256>("P-256"),
+        group::<group::P384>("P-384"),
+        group::<group::X25519>("Curve25519"),
+        kem::<kem::MlKem768>("ML-KEM-768"),
+        kem::<kem::MlKem1024>("ML-KEM-1024"),
+    ];
+    subjects.extend(Algorithm::ALL.into_iter().map(hybrid));
+    subjects
+}
+
+/// A difference between the draft and the code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// A constant has a different value in the draft
+    Value {
+        line: usize,
+        subject: String,
+        name: String,
+        draft: usize,
+        code: usize,
+    },
+    /// A constant defined in the code is not listed in the draft
+    Missing { subject: String, name: String },
+    /// A label differs from the code, or its ASCII and hex forms disagree
+    Label {
+        line: usize,
+        subject: String,
+        draft: String,
+        code: String,
+    },
+    /// A constant or label could not be attributed to a group, KEM, or hybrid KEM
+    UnknownSubject { line: usize, text: String },
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::Value {
+                line,
+                subject,
+                name,
+                draft,
+                code,
+            } => write!(
+                f,
+                "line {}: {} {}: draft={} code={}",
+                line, subject, name, draft, code
+            ),
+            Discrepancy::Missing { subject, name } => {
+                write!(f, "{} {}: missing from the draft", subject, name)
+            }
+            Discrepancy::Label {
+                line,
+                subject,
+                draft,
+                code,
+            } => write!(
+                f,
+                "line {}: {} Label: draft={} code={}",
+                line, subject, draft, code
+            ),
+            Discrepancy::UnknownSubject { line, text } => {
+                write!(f, "line {}: cannot tell what {:?} refers to", line, text)
+            }
+        }
+    }
+}
+
+impl Discrepancy {
+    /// Whether the discrepancy is listed in `ALLOWED`
+    pub fn is_allowed(&self) -> bool {
+        match self {
+            Discrepancy::Value {
+                subject,
+                name,
+                draft,
+                code,
+                ..
+            } => ALLOWED.contains(&(subject.as_str(), name.as_str(), *draft, *code)),
+            _ => false,
+        }
+    }
+}
+
+/// The subject named earliest in `line`, if any
+fn find_subject<'a>(subjects: &'a [Subject], line: &str) -> Option<&'a Subject> {
+    subjects
+        .iter()
+        .filter_map(|s| line.find(s.name).map(|i| (i, s)))
+        .min_by_key(|(i, _)| *i)
+        .map(|(_, s)| s)
+}
+
+/// Parse "- `Nek`: 1216"
+fn parse_constant(line: &str) -> Option<(&str, usize)> {
+    let (name, value) = line.strip_prefix("- `")?.split_once("`: ")?;
+    Some((name, value.trim().parse().ok()?))
+}
+
+/// Parse "* `Label`: `|-()-|` (0x7C2D28292D7C)" into the ASCII and hex forms of the label
+fn parse_label(line: &str) -> Option<(String, String)> {
+    let (ascii, hex) = line.strip_prefix("* `Label`: `")?.rsplit_once("` (0x")?;
+    let hex = hex.strip_suffix(')')?;
+    // Pipes are escaped so that kramdown does not read the line as a table
+    Some((ascii.replace("\\|", "|"), hex.to_lowercase()))
+}
+
+/// Compare the constants and labels in the draft with the code
+pub fn check(draft: &str) -> Vec<Discrepancy> {
+    let subjects = subjects();
+    let mut discrepancies = Vec::new();
+    let mut seen: Vec<(&str, &str)> = Vec::new();
+
+    let mut heading = "";
+    let mut intro = "";
+    for (i, line) in draft.lines().enumerate() {
+        let line_number = i + 1;
+        let subject = || find_subject(&subjects, intro).or(find_subject(&subjects, heading));
+
+        if line.starts_with('#') {
+            heading = line;
+            intro = "";
+        } else if let Some((name, value)) = parse_constant(line) {
+            if !CHECKED.contains(&name) {
+                continue;
+            }
+
+            let Some(subject) = subject() else {
+                discrepancies.push(Discrepancy::UnknownSubject {
+                    line: line_number,
+                    text: line.to_string(),
+                });
+                continue;
+            };
+
+            seen.push((subject.name, name));
+            match subject.constants.iter().find(|(n, _)| *n == name) {
+                Some(&(_, code)) if code != value => discrepancies.push(Discrepancy::Value {
+                    line: line_number,
+                    subject: subject.name.to_string(),
+                    name: name.to_string(),
+                    draft: value,
+                    code,
+                }),
+                Some(_) => {}
+                // A constant that the code does not have, e.g., Nek for a group
+                None => discrepancies.push(Discrepancy::UnknownSubject {
+                    line: line_number,
+                    text: line.to_string(),
+                }),
+            }
+        } else if let Some((ascii, hex)) = parse_label(line) {
+            let Some((subject, code)) = subject().and_then(|s| s.label.map(|l| (s, l))) else {
+                discrepancies.push(Discrepancy::UnknownSubject {
+                    line: line_number,
+                    text: line.to_string(),
+                });
+                continue;
+            };
+
+            let code_ascii = String::from_utf8_lossy(code);
+            if ascii != code_ascii || hex != hex::encode(code) {
+                discrepancies.push(Discrepancy::Label {
+                    line: line_number,
+                    subject: subject.name.to_string(),
+                    draft: format!("{} (0x{})", ascii, hex),
+                    code: format!("{} (0x{})", code_ascii, hex::encode(code)),
+                });
+            }
+        } else if !line.trim().is_empty() && !line.starts_with("- ") && !line.starts_with("* ") {
+            intro = line;
+        }
+    }
+
+    for subject in &subjects {
+        for &(name, _) in &subject.constants {
+            if !seen.contains(&(subject.name, name)) {
+                discrepancies.push(Discrepancy::Missing {
+                    subject: subject.name.to_string(),
+                    name: name.to_string(),
+                });
+            }
+        }
+    }
+
+    discrepancies
+}
+
+/// Replace each `{::include FILE}` line with the contents of `FILE`, read relative to `dir`.  Line
+/// numbers reported by `check` refer to the expanded text.
+pub fn expand_includes(draft: &str, dir: &Path) -> io::Result<String> {
+    let mut expanded = String::new();
+    for line in draft.lines() {
+        match line
+            .strip_prefix("{::include ")
+            .and_then(|l| l.strip_suffix('}'))
+        {
+            Some(file) => expanded += &fs::read_to_string(dir.join(file))?,
+            None => {
+                expanded += line;
+                expanded.push('\n');
+            }
+        }
+    }
+    Ok(expanded)
+}
+
+/// The section in which the draft defines a component
+fn reference(component: &str) -> &'static str {
+    match component {
+        "ML-KEM-768" | "ML-KEM-1024" => "{{mlkem}}",
+        "P-256" | "P-384" => "{{group-nist}}",
+        "Curve25519" => "{{group-curve25519}}",
+        "SHAKE-256" | "SHA3-256" => "{{FIPS202}}",
+        _ => panic!("No reference for {}", component),
+    }
+}
+
+/// Render the subsections of "Concrete Hybrid KEM Instances" from the registry and the instance
+/// types, for inclusion in the draft
+pub fn instance_sections() -> String {
+    let mut md = String::new();
+    for algorithm in Algorithm::ALL {
+        let entry = algorithm.registry_entry();
+        let kem = algorithm.kem();
+
+        if !md.is_empty() {
+            md += "\n";
+        }
+        md += &format!("## {}\n\n", algorithm.name());
+        md += &format!(
+            "This hybrid KEM combines {} with {} using the {} framework from\n{{{{HYBRID-KEMS}}}}.",
+            entry.pq, entry.t, entry.framework
+        );
+        if algorithm == Algorithm::MlKem768X25519 {
+            md += " It is identical to the X-Wing construction from {{XWING-SPEC}}.\n";
+        } else {
+            md += " ";
+        }
+        md += "It has the following components:\n\n";
+
+        md += &format!("* `KEM_PQ`: {} {}\n", entry.pq, reference(entry.pq));
+        md += &format!("* `Group_T`: {} {}\n", entry.t, reference(entry.t));
+        md += &format!("* `PRG`: {} {}\n", entry.prg, reference(entry.prg));
+        md += &format!("* `KDF`: {} {}\n", entry.kdf, reference(entry.kdf));
+        // Pipes are escaped so that kramdown does not read the line as a table
+        let label = String::from_utf8(entry.label.to_vec()).expect("Labels are ASCII");
+        md += &format!(
+            "* `Label`: `{}` (0x{})\n\n",
+            label.replace('|', "\\|"),
+            hex::encode_upper(entry.label)
+        );
+
+        md += "The KEM constants for the resulting hybrid KEM are as follows:\n\n";
+        md += &format!("- `Nseed`: {}\n", kem.seed_size());
+        md += &format!("- `Nek`: {}\n", kem.encapsulation_key_size());
+        md += &format!("- `Ndk`: {}\n", kem.decapsulation_key_size());
+        md += &format!("- `Nct`: {}\n", kem.ciphertext_size());
+        md += &format!("- `Nss`: {}\n", kem.shared_secret_size());
+    }
+    md
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MlKem768P256;
+
+    // A draft that lists every constant and label correctly
+    fn consistent_draft() -> String {
+        let mut draft = String::new();
+        for subject in subjects() {
+            draft += &format!(
+                "## {}\n\nThe following constants are defined:\n\n",
+                subject.name
+            );
+            if let Some(label) = subject.label {
+                let ascii = String::from_utf8_lossy(label).replace('|', "\\|");
+                draft += &format!("* `Label`: `{}` (0x{})\n\n", ascii, hex::encode(label));
+            }
+            for (name, value) in &subject.constants {
+                draft += &format!("- `{}`: {}\n", name, value);
+            }
+            draft += "\n";
+        }
+        draft
+    }
+
+    #[test]
+    fn consistent() {
+        assert_eq!(check(&consistent_draft()), vec![]);
+    }
+
+    #[test]
+    fn wrong_value() {
+        let draft = consistent_draft().replacen(
+            &format!("- `Nct`: {}", MlKem768P256::CIPHERTEXT_SIZE),
+            "- `Nct`: 1121",
+            1,
+        );
+        let discrepancies = check(&draft);
+        assert_eq!(discrepancies.len(), 1);
+        assert!(matches!(
+            &discrepancies[0],
+            Discrepancy::Value { subject, name, draft: 1121, .. }
+                if subject == "MLKEM768-P256" && name == "Nct"
+        ));
+    }
+
+    #[test]
+    fn missing_constant() {
+        let draft = consistent_draft().replacen("- `Nelem`: 32\n", "", 1);
+        assert_eq!(
+            check(&draft),
+            vec![Discrepancy::Missing {
+                subject: "Curve25519".to_string(),
+                name: "Nelem".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn wrong_label() {
+        // Correct ASCII, wrong hex
+        let draft = consistent_draft().replacen("(0x7c2d28292d7c)", "(0x7c2d28292d7d)", 1);
+        assert!(matches!(
+            &check(&draft)[..],
+            [Discrepancy::Label { subject, .. }] if subject == "MLKEM768-P256"
+        ));
+    }
+
+    // Intro lines name the subject when a section describes more than one
+    #[test]
+    fn intro_line() {
+        let draft = consistent_draft().replacen(
+            "## ML-KEM-1024\n\nThe following constants are defined:",
+            "The KEM constants for ML-KEM-1024 are as follows:",
+            1,
+        );
+        assert_eq!(check(&draft), vec![]);
+    }
+
+    #[test]
+    fn generated_sections() {
+        // Only the constants of the groups and component KEMs are missing
+        assert!(check(&instance_sections()).iter().all(
+            |d| matches!(d, Discrepancy::Missing { subject, .. } if !subject.starts_with("MLKEM"))
+        ));
+    }
+
+    // The sections included by the draft are up to date
+    #[test]
+    fn checked_in_sections() {
+        let instances = include_str!("../../instances.md");
+        assert_eq!(instances, instance_sections());
+    }
+
+    // Only the differences listed in ALLOWED remain, and each of them is still present
+    #[test]
+    fn checked_in_draft() {
+        let dir = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/.."));
+        let draft =
+            fs::read_to_string(dir.join("draft-irtf-cfrg-concrete-hybrid-kems.md")).unwrap();
+        let draft = expand_includes(&draft, dir).unwrap();
+        let discrepancies = check(&draft);
+        assert!(
+            discrepancies.iter().all(Discrepancy::is_allowed),
+            "{:#?}",
+            discrepancies
+        );
+        assert_eq!(discrepancies.len(), ALLOWED.len(), "{:#?}", discrepancies);
+    }
+
+    #[test]
+    fn allowed() {
+        let value = |draft| Discrepancy::Value {
+            line: 1,
+            subject: "P-256".to_string(),
+            name: "Nseed".to_string(),
+            draft,
+            code: 32,
+        };
+        assert!(value(128).is_allowed());
+        assert!(!value(64).is_allowed());
+        assert!(
+            !Discrepancy::Missing {
+                subject: "P-256".to_string(),
+                name: "Nseed".to_string(),
+            }
+            .is_allowed()
+        );
+    }
+}
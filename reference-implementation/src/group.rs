@@ -1,4 +1,6 @@
 use crate::kem::{Seed, SeedSize, SharedSecret, SharedSecretSize};
+use alloc::vec;
+use alloc::vec::Vec;
 use elliptic_curve::Curve;
 use hex_literal::hex;
 use hybrid_array::typenum::Unsigned;
@@ -15,6 +17,19 @@ pub trait NominalGroup: SeedSize + SharedSecretSize {
     fn random_scalar(seed: &Seed) -> Scalar;
     fn exp(element: &Element, scalar: &Scalar) -> Element;
     fn element_to_shared_secret(element: &Element) -> SharedSecret;
+
+    /// Whether `element` is the encoding of a group element.  `exp` panics on anything else, so
+    /// elements from untrusted sources should be checked first.
+    fn validate_element(element: &[u8]) -> bool;
+}
+
+/// Nominal groups that can operate on caller-provided buffers without allocating.  The buffers
+/// must have exactly the sizes given by the `NominalGroup` constants.
+pub trait HeaplessGroup: NominalGroup {
+    fn generator_into(element: &mut [u8]);
+    fn random_scalar_into(seed: &[u8], scalar: &mut [u8]);
+    fn exp_into(element: &[u8], scalar: &[u8], output: &mut [u8]);
+    fn element_to_shared_secret_into(element: &[u8], ss: &mut [u8]);
 }
 
 pub struct X25519;
@@ -33,16 +48,49 @@ impl NominalGroup for X25519 {
     const ELEMENT_SIZE: usize = 32;
 
     fn generator() -> Element {
-        // X25519 generator is 9
-        hex!("0900000000000000000000000000000000000000000000000000000000000000").to_vec()
+        let mut element = vec![0; Self::ELEMENT_SIZE];
+        Self::generator_into(&mut element);
+        element
     }
 
     fn random_scalar(seed: &Seed) -> Scalar {
-        assert_eq!(seed.len(), Self::SEED_SIZE);
-        seed.to_vec()
+        let mut scalar = vec![0; Self::SCALAR_SIZE];
+        Self::random_scalar_into(seed, &mut scalar);
+        scalar
     }
 
     fn exp(element: &Element, scalar: &Scalar) -> Element {
+        let mut output = vec![0; Self::ELEMENT_SIZE];
+        Self::exp_into(element, scalar, &mut output);
+        output
+    }
+
+    fn element_to_shared_secret(element: &Element) -> SharedSecret {
+        let mut ss = vec![0; Self::SHARED_SECRET_SIZE];
+        Self::element_to_shared_secret_into(element, &mut ss);
+        ss
+    }
+
+    // Every 32-byte string is a u-coordinate
+    fn validate_element(element: &[u8]) -> bool {
+        element.len() == Self::ELEMENT_SIZE
+    }
+}
+
+impl HeaplessGroup for X25519 {
+    fn generator_into(element: &mut [u8]) {
+        // X25519 generator is 9
+        element.copy_from_slice(&hex!(
+            "0900000000000000000000000000000000000000000000000000000000000000"
+        ));
+    }
+
+    fn random_scalar_into(seed: &[u8], scalar: &mut [u8]) {
+        assert_eq!(seed.len(), Self::SEED_SIZE);
+        scalar.copy_from_slice(seed);
+    }
+
+    fn exp_into(element: &[u8], scalar: &[u8], output: &mut [u8]) {
         assert_eq!(element.len(), Self::ELEMENT_SIZE);
         assert_eq!(scalar.len(), Self::SCALAR_SIZE);
 
@@ -56,12 +104,12 @@ impl NominalGroup for X25519 {
 
         // Compute the Diffie-Hellman operation
         let shared_secret = secret.diffie_hellman(&public);
-        shared_secret.as_bytes().to_vec()
+        output.copy_from_slice(shared_secret.as_bytes());
     }
 
-    fn element_to_shared_secret(element: &Element) -> SharedSecret {
+    fn element_to_shared_secret_into(element: &[u8], ss: &mut [u8]) {
         assert_eq!(element.len(), Self::ELEMENT_SIZE);
-        element.clone()
+        ss.copy_from_slice(element);
     }
 }
 
@@ -123,26 +171,61 @@ macro_rules! define_nist_group {
             const ELEMENT_SIZE: usize = 1 + 2 * Self::SCALAR_SIZE;
 
             fn generator() -> Element {
+                let mut element = vec![0; Self::ELEMENT_SIZE];
+                Self::generator_into(&mut element);
+                element
+            }
+
+            fn random_scalar(seed: &Seed) -> Scalar {
+                let mut scalar = vec![0; Self::SCALAR_SIZE];
+                Self::random_scalar_into(seed, &mut scalar);
+                scalar
+            }
+
+            fn exp(element: &Element, scalar: &Scalar) -> Element {
+                let mut output = vec![0; Self::ELEMENT_SIZE];
+                Self::exp_into(element, scalar, &mut output);
+                output
+            }
+
+            fn element_to_shared_secret(element: &Element) -> SharedSecret {
+                let mut ss = vec![0; Self::SHARED_SECRET_SIZE];
+                Self::element_to_shared_secret_into(element, &mut ss);
+                ss
+            }
+
+            // An uncompressed SEC1 encoding of a point on the curve
+            fn validate_element(element: &[u8]) -> bool {
+                use $mod::{AffinePoint, EncodedPoint, elliptic_curve::sec1::FromEncodedPoint};
+
+                element.len() == Self::ELEMENT_SIZE
+                    && EncodedPoint::from_bytes(element)
+                        .is_ok_and(|point| AffinePoint::from_encoded_point(&point).is_some().into())
+            }
+        }
+
+        impl HeaplessGroup for $group {
+            fn generator_into(element: &mut [u8]) {
                 use $mod::{
                     elliptic_curve::sec1::ToEncodedPoint, elliptic_curve::Group, AffinePoint,
                     ProjectivePoint,
                 };
 
                 let gen_aff: AffinePoint = ProjectivePoint::generator().into();
-                gen_aff.to_encoded_point(false).as_bytes().to_vec()
+                element.copy_from_slice(gen_aff.to_encoded_point(false).as_bytes());
             }
 
-            fn random_scalar(seed: &Seed) -> Scalar {
+            fn random_scalar_into(seed: &[u8], scalar: &mut [u8]) {
                 use $mod::NonZeroScalar;
                 assert_eq!(seed.len(), Self::SEED_SIZE);
 
                 // Coincidentally, NonZeroScalar::random implements exactly the rejection sampling
                 // loop we need here.
                 let mut rng = Shake256Rng::new(seed);
-                NonZeroScalar::random(&mut rng).to_bytes().to_vec()
+                scalar.copy_from_slice(&NonZeroScalar::random(&mut rng).to_bytes());
             }
 
-            fn exp(element: &Element, scalar: &Scalar) -> Element {
+            fn exp_into(element: &[u8], scalar: &[u8], output: &mut [u8]) {
                 use $mod::{
                     elliptic_curve::ops::Reduce,
                     elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint},
@@ -163,16 +246,16 @@ macro_rules! define_nist_group {
                 let result_aff: AffinePoint = result_proj.into();
 
                 // Encode in uncompressed form
-                result_aff.to_encoded_point(false).as_bytes().to_vec()
+                output.copy_from_slice(result_aff.to_encoded_point(false).as_bytes());
             }
 
-            fn element_to_shared_secret(element: &Element) -> SharedSecret {
+            fn element_to_shared_secret_into(element: &[u8], ss: &mut [u8]) {
                 use $mod::EncodedPoint;
 
                 assert_eq!(element.len(), Self::ELEMENT_SIZE);
                 let encoded = EncodedPoint::from_bytes(element).expect("Invalid point encoding");
                 let x_bytes = encoded.x().expect("Point at infinity");
-                x_bytes.to_vec()
+                ss.copy_from_slice(x_bytes);
             }
         }
     };
@@ -267,20 +350,43 @@ mod test {
         assert_eq!(elem1, elem2, "Exponentiation should be deterministic");
     }
 
+    pub fn test_validation<G: NominalGroup>() {
+        let element = G::exp(&G::generator(), &G::random_scalar(&vec![3u8; G::SEED_SIZE]));
+        assert!(G::validate_element(&G::generator()));
+        assert!(G::validate_element(&element));
+        assert!(!G::validate_element(&element[1..]));
+        assert!(!G::validate_element(&[element.as_slice(), &[0]].concat()));
+    }
+
     fn test_all<G: NominalGroup>() {
         test_basic_ops::<G>();
         test_diffie_hellman::<G>();
         test_determinism::<G>();
+        test_validation::<G>();
+    }
+
+    // Points off the curve, and encodings other than the uncompressed one, are rejected
+    fn test_nist_validation<G: NominalGroup>() {
+        let mut element = G::generator();
+        element[G::ELEMENT_SIZE - 1] ^= 1;
+        assert!(!G::validate_element(&element));
+
+        let mut element = G::generator();
+        element[0] = 0x02;
+        assert!(!G::validate_element(&element));
+        assert!(!G::validate_element(&vec![0; G::ELEMENT_SIZE]));
     }
 
     #[test]
     fn p256() {
         test_all::<P256>();
+        test_nist_validation::<P256>();
     }
 
     #[test]
     fn p384() {
         test_all::<P384>();
+        test_nist_validation::<P384>();
     }
 
     #[test]
@@ -0,0 +1,554 @@
+//! Hybrid KEM operations on caller-provided buffers
+//!
+//! `HeaplessHybridKem` runs the GU and GC frameworks without touching the heap.  Keys,
+//! ciphertexts and shared secrets are fixed-size arrays owned by the caller, whose lengths are
+//! checked against the instance constants at compile time.  Intermediate values live in stack
+//! buffers, and the KDF input is streamed from wherever the values already are.
+//!
+//! ~~~text
+//! let mut ek = [0; MlKem768X25519::ENCAPSULATION_KEY_SIZE];
+//! MlKem768X25519::derive_encapsulation_key_into(&seed, &mut ek);
+//! ~~~
+
+use crate::group::HeaplessGroup;
+use crate::hybrid::{
+    GC, GU, HybridKem, HybridKemConstants, c2pri_combiner_into, universal_combiner_into,
+};
+use crate::kdf::Kdf;
+use crate::kem::{EncapsDerand, HeaplessKem, Kem, PqKem, SeedSize, SharedSecretSize};
+use crate::prg::Prg;
+use rand::CryptoRng;
+
+// Upper bounds on the component sizes, which size the scratch buffers.  They cover every
+// component in this crate; instances with larger components fail to compile.
+const MAX_PQ_SEED_SIZE: usize = 64;
+const MAX_PQ_DECAPSULATION_KEY_SIZE: usize = 64;
+const MAX_PQ_ENCAPSULATION_KEY_SIZE: usize = 1568;
+const MAX_PQ_RANDOMNESS_SIZE: usize = 32;
+const MAX_PQ_SHARED_SECRET_SIZE: usize = 32;
+const MAX_T_SEED_SIZE: usize = 48;
+const MAX_SCALAR_SIZE: usize = 48;
+const MAX_ELEMENT_SIZE: usize = 97;
+const MAX_T_SHARED_SECRET_SIZE: usize = 48;
+
+/// Hybrid KEMs whose operations write into caller-provided arrays without allocating.  They
+/// produce the same outputs as the corresponding `Kem`, `EncapsDerand` and `KemWithContext`
+/// methods.
+pub trait HeaplessHybridKem: HybridKem {
+    /// Derive the encapsulation key for `seed`, which is itself the decapsulation key
+    fn derive_encapsulation_key_into<const S: usize, const EK: usize>(
+        seed: &[u8; S],
+        ek: &mut [u8; EK],
+    );
+
+    fn encaps_into<const EK: usize, const CT: usize, const SS: usize>(
+        ek: &[u8; EK],
+        ct: &mut [u8; CT],
+        ss: &mut [u8; SS],
+        rng: &mut impl CryptoRng,
+    );
+
+    fn encaps_derand_into<const EK: usize, const R: usize, const CT: usize, const SS: usize>(
+        ek: &[u8; EK],
+        randomness: &[u8; R],
+        ct: &mut [u8; CT],
+        ss: &mut [u8; SS],
+    );
+
+    fn decaps_into<const DK: usize, const CT: usize, const SS: usize>(
+        dk: &[u8; DK],
+        ct: &[u8; CT],
+        ss: &mut [u8; SS],
+    );
+
+    fn encaps_with_context_into<const EK: usize, const CT: usize, const SS: usize>(
+        ek: &[u8; EK],
+        context: &[u8],
+        ct: &mut [u8; CT],
+        ss: &mut [u8; SS],
+        rng: &mut impl CryptoRng,
+    );
+
+    fn encaps_derand_with_context_into<
+        const EK: usize,
+        const R: usize,
+        const CT: usize,
+        const SS: usize,
+    >(
+        ek: &[u8; EK],
+        context: &[u8],
+        randomness: &[u8; R],
+        ct: &mut [u8; CT],
+        ss: &mut [u8; SS],
+    );
+
+    fn decaps_with_context_into<const DK: usize, const CT: usize, const SS: usize>(
+        dk: &[u8; DK],
+        ct: &[u8; CT],
+        context: &[u8],
+        ss: &mut [u8; SS],
+    );
+}
+
+fn check_bounds<PQ: HeaplessKem, T: HeaplessGroup>() {
+    const {
+        assert!(PQ::SEED_SIZE <= MAX_PQ_SEED_SIZE);
+        assert!(PQ::DECAPSULATION_KEY_SIZE <= MAX_PQ_DECAPSULATION_KEY_SIZE);
+        assert!(PQ::ENCAPSULATION_KEY_SIZE <= MAX_PQ_ENCAPSULATION_KEY_SIZE);
+        assert!(PQ::RANDOMNESS_SIZE <= MAX_PQ_RANDOMNESS_SIZE);
+        assert!(PQ::SHARED_SECRET_SIZE <= MAX_PQ_SHARED_SECRET_SIZE);
+        assert!(T::SEED_SIZE <= MAX_T_SEED_SIZE);
+        assert!(T::SCALAR_SIZE <= MAX_SCALAR_SIZE);
+        assert!(T::ELEMENT_SIZE <= MAX_ELEMENT_SIZE);
+        assert!(T::SHARED_SECRET_SIZE <= MAX_T_SHARED_SECRET_SIZE);
+    }
+}
+
+/// The combiner inputs, wherever they are stored
+struct Components<'a> {
+    ss_pq: &'a [u8],
+    ss_t: &'a [u8],
+    ct_pq: &'a [u8],
+    ct_t: &'a [u8],
+    ek_pq: &'a [u8],
+    ek_t: &'a [u8],
+}
+
+/// A framework's combiner, applied with the instance's KDF and label
+type Combine = fn(&Components, Option<&[u8]>, &mut [u8]);
+
+fn universal<K: Kdf, C: HybridKemConstants>(
+    v: &Components,
+    context: Option<&[u8]>,
+    ss_h: &mut [u8],
+) {
+    universal_combiner_into::<K>(
+        v.ss_pq,
+        v.ss_t,
+        v.ct_pq,
+        v.ct_t,
+        v.ek_pq,
+        v.ek_t,
+        C::LABEL,
+        context,
+        ss_h,
+    );
+}
+
+fn c2pri<K: Kdf, C: HybridKemConstants>(v: &Components, context: Option<&[u8]>, ss_h: &mut [u8]) {
+    c2pri_combiner_into::<K>(v.ss_pq, v.ss_t, v.ct_t, v.ek_t, C::LABEL, context, ss_h);
+}
+
+fn expand_decaps_key<PQ: HeaplessKem, T: HeaplessGroup, P: Prg>(
+    seed: &[u8],
+    dk_pq: &mut [u8],
+    ek_pq: &mut [u8],
+    dk_t: &mut [u8],
+    ek_t: &mut [u8],
+) {
+    let mut seed_full = [0; MAX_PQ_SEED_SIZE + MAX_T_SEED_SIZE];
+    let seed_full = &mut seed_full[..PQ::SEED_SIZE + T::SEED_SIZE];
+    P::generate(seed, seed_full);
+    let (seed_pq, seed_t) = seed_full.split_at(PQ::SEED_SIZE);
+
+    PQ::derive_key_pair_into(seed_pq, dk_pq, ek_pq);
+
+    let mut generator = [0; MAX_ELEMENT_SIZE];
+    let generator = &mut generator[..T::ELEMENT_SIZE];
+    T::generator_into(generator);
+    T::random_scalar_into(seed_t, dk_t);
+    T::exp_into(generator, dk_t, ek_t);
+}
+
+fn derive_encapsulation_key<PQ: HeaplessKem, T: HeaplessGroup, P: Prg>(seed: &[u8], ek: &mut [u8]) {
+    check_bounds::<PQ, T>();
+
+    let mut dk_pq = [0; MAX_PQ_DECAPSULATION_KEY_SIZE];
+    let mut dk_t = [0; MAX_SCALAR_SIZE];
+    let (ek_pq, ek_t) = ek.split_at_mut(PQ::ENCAPSULATION_KEY_SIZE);
+    expand_decaps_key::<PQ, T, P>(
+        seed,
+        &mut dk_pq[..PQ::DECAPSULATION_KEY_SIZE],
+        ek_pq,
+        &mut dk_t[..T::SCALAR_SIZE],
+        ek_t,
+    );
+}
+
+fn encaps_derand<PQ: HeaplessKem, T: HeaplessGroup>(
+    ek: &[u8],
+    randomness: &[u8],
+    ct: &mut [u8],
+    ss_h: &mut [u8],
+    combine: Combine,
+    context: Option<&[u8]>,
+) {
+    check_bounds::<PQ, T>();
+
+    let (ek_pq, ek_t) = ek.split_at(PQ::ENCAPSULATION_KEY_SIZE);
+    let (randomness_pq, seed_e) = randomness.split_at(PQ::RANDOMNESS_SIZE);
+    let (ct_pq, ct_t) = ct.split_at_mut(PQ::CIPHERTEXT_SIZE);
+
+    let mut ss_pq = [0; MAX_PQ_SHARED_SECRET_SIZE];
+    let ss_pq = &mut ss_pq[..PQ::SHARED_SECRET_SIZE];
+    PQ::encaps_derand_into(ek_pq, randomness_pq, ct_pq, ss_pq);
+
+    let mut sk_e = [0; MAX_SCALAR_SIZE];
+    let sk_e = &mut sk_e[..T::SCALAR_SIZE];
+    let mut generator = [0; MAX_ELEMENT_SIZE];
+    let generator = &mut generator[..T::ELEMENT_SIZE];
+    let mut dh = [0; MAX_ELEMENT_SIZE];
+    let dh = &mut dh[..T::ELEMENT_SIZE];
+    let mut ss_t = [0; MAX_T_SHARED_SECRET_SIZE];
+    let ss_t = &mut ss_t[..T::SHARED_SECRET_SIZE];
+    T::random_scalar_into(seed_e, sk_e);
+    T::generator_into(generator);
+    T::exp_into(generator, sk_e, ct_t);
+    T::exp_into(ek_t, sk_e, dh);
+    T::element_to_shared_secret_into(dh, ss_t);
+
+    let components = Components {
+        ss_pq,
+        ss_t,
+        ct_pq,
+        ct_t,
+        ek_pq,
+        ek_t,
+    };
+    combine(&components, context, ss_h);
+}
+
+fn decaps<PQ: HeaplessKem, T: HeaplessGroup, P: Prg>(
+    dk: &[u8],
+    ct: &[u8],
+    ss_h: &mut [u8],
+    combine: Combine,
+    context: Option<&[u8]>,
+) {
+    check_bounds::<PQ, T>();
+
+    let (ct_pq, ct_t) = ct.split_at(PQ::CIPHERTEXT_SIZE);
+
+    let mut dk_pq = [0; MAX_PQ_DECAPSULATION_KEY_SIZE];
+    let dk_pq = &mut dk_pq[..PQ::DECAPSULATION_KEY_SIZE];
+    let mut ek_pq = [0; MAX_PQ_ENCAPSULATION_KEY_SIZE];
+    let ek_pq = &mut ek_pq[..PQ::ENCAPSULATION_KEY_SIZE];
+    let mut dk_t = [0; MAX_SCALAR_SIZE];
+    let dk_t = &mut dk_t[..T::SCALAR_SIZE];
+    let mut ek_t = [0; MAX_ELEMENT_SIZE];
+    let ek_t = &mut ek_t[..T::ELEMENT_SIZE];
+    expand_decaps_key::<PQ, T, P>(dk, dk_pq, ek_pq, dk_t, ek_t);
+
+    let mut ss_pq = [0; MAX_PQ_SHARED_SECRET_SIZE];
+    let ss_pq = &mut ss_pq[..PQ::SHARED_SECRET_SIZE];
+    PQ::decaps_into(dk_pq, ct_pq, ss_pq);
+
+    let mut dh = [0; MAX_ELEMENT_SIZE];
+    let dh = &mut dh[..T::ELEMENT_SIZE];
+    let mut ss_t = [0; MAX_T_SHARED_SECRET_SIZE];
+    let ss_t = &mut ss_t[..T::SHARED_SECRET_SIZE];
+    T::exp_into(ct_t, dk_t, dh);
+    T::element_to_shared_secret_into(dh, ss_t);
+
+    let components = Components {
+        ss_pq,
+        ss_t,
+        ct_pq,
+        ct_t,
+        ek_pq,
+        ek_t,
+    };
+    combine(&components, context, ss_h);
+}
+
+macro_rules! impl_heapless_hybrid_kem {
+    ($framework:ident, $combiner:ident) => {
+        impl<PQ, T, P, K, C> $framework<PQ, T, P, K, C>
+        where
+            PQ: PqKem + HeaplessKem,
+            T: HeaplessGroup,
+            P: Prg,
+            K: Kdf,
+            C: HybridKemConstants,
+        {
+            fn encaps_heapless<const EK: usize, const CT: usize, const SS: usize>(
+                ek: &[u8; EK],
+                context: Option<&[u8]>,
+                ct: &mut [u8; CT],
+                ss: &mut [u8; SS],
+                rng: &mut impl CryptoRng,
+            ) {
+                let mut randomness = [0; MAX_PQ_RANDOMNESS_SIZE + MAX_T_SEED_SIZE];
+                let randomness = &mut randomness[..Self::RANDOMNESS_SIZE];
+                rng.fill_bytes(randomness);
+                Self::encaps_derand_heapless(ek, context, randomness, ct, ss);
+            }
+
+            fn encaps_derand_heapless<const EK: usize, const CT: usize, const SS: usize>(
+                ek: &[u8; EK],
+                context: Option<&[u8]>,
+                randomness: &[u8],
+                ct: &mut [u8; CT],
+                ss: &mut [u8; SS],
+            ) {
+                const {
+                    assert!(EK == Self::ENCAPSULATION_KEY_SIZE);
+                    assert!(CT == Self::CIPHERTEXT_SIZE);
+                    assert!(SS == Self::SHARED_SECRET_SIZE);
+                    assert!(SS == K::OUTPUT_SIZE);
+                }
+                encaps_derand::<PQ, T>(ek, randomness, ct, ss, $combiner::<K, C>, context);
+            }
+
+            fn decaps_heapless<const DK: usize, const CT: usize, const SS: usize>(
+                dk: &[u8; DK],
+                ct: &[u8; CT],
+                context: Option<&[u8]>,
+                ss: &mut [u8; SS],
+            ) {
+                const {
+                    assert!(DK == Self::DECAPSULATION_KEY_SIZE);
+                    assert!(CT == Self::CIPHERTEXT_SIZE);
+                    assert!(SS == Self::SHARED_SECRET_SIZE);
+                    assert!(SS == K::OUTPUT_SIZE);
+                }
+                decaps::<PQ, T, P>(dk, ct, ss, $combiner::<K, C>, context);
+            }
+        }
+
+        impl<PQ, T, P, K, C> HeaplessHybridKem for $framework<PQ, T, P, K, C>
+        where
+            PQ: PqKem + HeaplessKem,
+            T: HeaplessGroup,
+            P: Prg,
+            K: Kdf,
+            C: HybridKemConstants,
+        {
+            fn derive_encapsulation_key_into<const S: usize, const EK: usize>(
+                seed: &[u8; S],
+                ek: &mut [u8; EK],
+            ) {
+                const {
+                    assert!(S == Self::SEED_SIZE);
+                    assert!(EK == Self::ENCAPSULATION_KEY_SIZE);
+                }
+                derive_encapsulation_key::<PQ, T, P>(seed, ek);
+            }
+
+            fn encaps_into<const EK: usize, const CT: usize, const SS: usize>(
+                ek: &[u8; EK],
+                ct: &mut [u8; CT],
+                ss: &mut [u8; SS],
+                rng: &mut impl CryptoRng,
+            ) {
+                Self::encaps_heapless(ek, None, ct, ss, rng);
+            }
+
+            fn encaps_derand_into<
+                const EK: usize,
+                const R: usize,
+                const CT: usize,
+                const SS: usize,
+            >(
+                ek: &[u8; EK],
+                randomness: &[u8; R],
+                ct: &mut [u8; CT],
+                ss: &mut [u8; SS],
+            ) {
+                const { assert!(R == Self::RANDOMNESS_SIZE) }
+                Self::encaps_derand_heapless(ek, None, randomness, ct, ss);
+            }
+
+            fn decaps_into<const DK: usize, const CT: usize, const SS: usize>(
+                dk: &[u8; DK],
+                ct: &[u8; CT],
+                ss: &mut [u8; SS],
+            ) {
+                Self::decaps_heapless(dk, ct, None, ss);
+            }
+
+            fn encaps_with_context_into<const EK: usize, const CT: usize, const SS: usize>(
+                ek: &[u8; EK],
+                context: &[u8],
+                ct: &mut [u8; CT],
+                ss: &mut [u8; SS],
+                rng: &mut impl CryptoRng,
+            ) {
+                Self::encaps_heapless(ek, Some(context), ct, ss, rng);
+            }
+
+            fn encaps_derand_with_context_into<
+                const EK: usize,
+                const R: usize,
+                const CT: usize,
+                const SS: usize,
+            >(
+                ek: &[u8; EK],
+                context: &[u8],
+                randomness: &[u8; R],
+                ct: &mut [u8; CT],
+                ss: &mut [u8; SS],
+            ) {
+                const { assert!(R == Self::RANDOMNESS_SIZE) }
+                Self::encaps_derand_heapless(ek, Some(context), randomness, ct, ss);
+            }
+
+            fn decaps_with_context_into<const DK: usize, const CT: usize, const SS: usize>(
+                dk: &[u8; DK],
+                ct: &[u8; CT],
+                context: &[u8],
+                ss: &mut [u8; SS],
+            ) {
+                Self::decaps_heapless(dk, ct, Some(context), ss);
+            }
+        }
+    };
+}
+
+impl_heapless_hybrid_kem! { GU, universal }
+impl_heapless_hybrid_kem! { GC, c2pri }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::group::{NominalGroup, P256, P384, X25519};
+    use crate::hybrid::EncapsDerandWithContext;
+    use crate::hybrid::test::TestConstants;
+    use crate::kdf::Sha3_256;
+    use crate::kem::{MlKem768, MlKem1024};
+    use crate::prg::Shake256;
+    use crate::{MlKem768P256, MlKem768X25519, MlKem1024P384};
+    use rand::{SeedableRng, rngs::StdRng};
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    // Counts the allocations made by the current thread, so that tests running in parallel do
+    // not interfere
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.with(|count| count.set(count.get() + 1));
+            unsafe { System.alloc(layout) }
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            unsafe { System.dealloc(ptr, layout) }
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+        let before = ALLOCATIONS.with(Cell::get);
+        let result = f();
+        (result, ALLOCATIONS.with(Cell::get) - before)
+    }
+
+    type TestGU = GU<MlKem768, X25519, Shake256, Sha3_256, TestConstants>;
+    type TestGU384 = GU<MlKem1024, P384, Shake256, Sha3_256, TestConstants>;
+    type TestGC256 = GC<MlKem768, P256, Shake256, Sha3_256, TestConstants>;
+
+    fn test_heapless<
+        H: HeaplessHybridKem + EncapsDerandWithContext,
+        const S: usize,
+        const EK: usize,
+        const R: usize,
+        const CT: usize,
+        const SS: usize,
+    >() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let seed = [3u8; S];
+        let randomness = [4u8; R];
+        let mut ek = [0u8; EK];
+        let mut ct = [0u8; CT];
+        let mut ss = [0u8; SS];
+        let mut ss2 = [0u8; SS];
+
+        let ((), count) = allocations(|| {
+            H::derive_encapsulation_key_into(&seed, &mut ek);
+            H::encaps_derand_into(&ek, &randomness, &mut ct, &mut ss);
+            H::decaps_into(&seed, &ct, &mut ss2);
+        });
+        assert_eq!(count, 0, "heapless operations should not allocate");
+        assert_eq!(ss, ss2);
+
+        // The outputs match the allocating API
+        let (dk_vec, ek_vec, _) = H::derive_key_pair(&seed);
+        assert_eq!(dk_vec, seed);
+        assert_eq!(ek_vec, ek);
+        let (ct_vec, ss_vec) = H::encaps_derand(&ek_vec, &randomness);
+        assert_eq!(ct_vec, ct);
+        assert_eq!(ss_vec, ss);
+
+        let ((), count) = allocations(|| {
+            H::encaps_into(&ek, &mut ct, &mut ss, &mut rng);
+            H::decaps_into(&seed, &ct, &mut ss2);
+        });
+        assert_eq!(count, 0, "heapless operations should not allocate");
+        assert_eq!(ss, ss2);
+        assert_eq!(H::decaps(&dk_vec, &ct.to_vec()), ss);
+
+        // Implicit rejection also runs without allocating
+        ct[0] ^= 1;
+        let ((), count) = allocations(|| H::decaps_into(&seed, &ct, &mut ss2));
+        assert_eq!(count, 0, "heapless operations should not allocate");
+        assert_ne!(ss, ss2);
+        assert_eq!(H::decaps(&dk_vec, &ct.to_vec()), ss2);
+
+        // The context is bound into the shared secret as by the allocating API
+        let ((), count) = allocations(|| {
+            H::encaps_derand_with_context_into(&ek, b"context", &randomness, &mut ct, &mut ss);
+            H::decaps_with_context_into(&seed, &ct, b"context", &mut ss2);
+        });
+        assert_eq!(count, 0, "heapless operations should not allocate");
+        assert_eq!(ss, ss2);
+        let (ct_vec, ss_vec) = H::encaps_derand_with_context(&ek_vec, b"context", &randomness);
+        assert_eq!(ct_vec, ct);
+        assert_eq!(ss_vec, ss);
+        assert_ne!(ss_vec, H::decaps(&dk_vec, &ct_vec));
+
+        H::encaps_with_context_into(&ek, b"context", &mut ct, &mut ss, &mut rng);
+        assert_eq!(
+            H::decaps_with_context(&dk_vec, &ct.to_vec(), b"context"),
+            ss
+        );
+    }
+
+    #[test]
+    fn counting_allocator() {
+        let (v, count) = allocations(|| vec![0u8; 16]);
+        assert_eq!(v.len(), 16);
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn instances() {
+        test_heapless::<MlKem768P256, 32, 1249, 64, 1153, 32>();
+        test_heapless::<MlKem768X25519, 32, 1216, 64, 1120, 32>();
+        test_heapless::<MlKem1024P384, 32, 1665, 80, 1665, 32>();
+    }
+
+    #[test]
+    fn frameworks() {
+        test_heapless::<TestGU, 32, 1216, 64, 1120, 32>();
+        test_heapless::<TestGU384, 32, 1665, 80, 1665, 32>();
+        test_heapless::<TestGC256, 32, 1249, 64, 1153, 32>();
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(MlKem768P256::RANDOMNESS_SIZE, 64);
+        assert_eq!(MlKem1024P384::RANDOMNESS_SIZE, 80);
+        assert_eq!(
+            <MlKem1024 as Kem>::ENCAPSULATION_KEY_SIZE,
+            MAX_PQ_ENCAPSULATION_KEY_SIZE
+        );
+        assert_eq!(P384::ELEMENT_SIZE, MAX_ELEMENT_SIZE);
+    }
+}
@@ -2,10 +2,12 @@ use crate::group::*;
 use crate::kdf::*;
 use crate::kem::*;
 use crate::prg::*;
+use alloc::vec;
+use alloc::vec::Vec;
 use rand::{CryptoRng, Rng};
 
 /// Split a vector into two parts
-fn split(v: &[u8], m: usize, n: usize) -> (Vec<u8>, Vec<u8>) {
+pub(crate) fn split(v: &[u8], m: usize, n: usize) -> (Vec<u8>, Vec<u8>) {
     assert_eq!(
         v.len(),
         m + n,
@@ -37,13 +39,13 @@ fn prepare_encaps_group<PQ: PqKem, T: NominalGroup>(
     ek_t: &Element,
     rng: &mut impl CryptoRng,
 ) -> (SharedSecret, SharedSecret, Ciphertext, Element) {
-    let (ss_pq, ct_pq) = PQ::encaps(&ek_pq, rng);
+    let (ss_pq, ct_pq) = PQ::encaps(ek_pq, rng);
 
     let mut seed_e = vec![0u8; T::SEED_SIZE];
     rng.fill(seed_e.as_mut_slice());
     let sk_e = T::random_scalar(&seed_e);
     let ct_t = T::exp(&T::generator(), &sk_e);
-    let ss_t = T::element_to_shared_secret(&T::exp(&ek_t, &sk_e));
+    let ss_t = T::element_to_shared_secret(&T::exp(ek_t, &sk_e));
 
     (ss_pq, ss_t, ct_pq, ct_t)
 }
@@ -63,11 +65,11 @@ fn prepare_encaps_group_derand<PQ: PqKem + EncapsDerand, T: NominalGroup>(
 
     let (randomness_pq, seed_e) = split(randomness, PQ::RANDOMNESS_SIZE, T::SEED_SIZE);
 
-    let (ct_pq, ss_pq) = PQ::encaps_derand(&ek_pq, &randomness_pq);
+    let (ct_pq, ss_pq) = PQ::encaps_derand(ek_pq, &randomness_pq);
 
     let sk_e = T::random_scalar(&seed_e);
     let ct_t = T::exp(&T::generator(), &sk_e);
-    let ss_t = T::element_to_shared_secret(&T::exp(&ek_t, &sk_e));
+    let ss_t = T::element_to_shared_secret(&T::exp(ek_t, &sk_e));
 
     (ss_pq, ss_t, ct_pq, ct_t)
 }
@@ -106,8 +108,8 @@ fn prepare_encaps_kem<PQ: PqKem, T: TKem>(
     ek_t: &EncapsulationKey,
     rng: &mut impl CryptoRng,
 ) -> (SharedSecret, SharedSecret, Ciphertext, Ciphertext) {
-    let (ss_pq, ct_pq) = PQ::encaps(&ek_pq, rng);
-    let (ss_t, ct_t) = T::encaps(&ek_t, rng);
+    let (ss_pq, ct_pq) = PQ::encaps(ek_pq, rng);
+    let (ss_t, ct_t) = T::encaps(ek_t, rng);
     (ss_pq, ss_t, ct_pq, ct_t)
 }
 
@@ -122,7 +124,20 @@ fn prepare_decaps_kem<PQ: PqKem, T: TKem>(
     (ss_pq, ss_t)
 }
 
-fn universal_combiner<K: Kdf>(
+/// Feed an application context to the KDF as `I2OSP(len(context), 8) || context`.  Without a
+/// context nothing is fed, so the combiners match the specified frameworks.
+fn update_context<K: Kdf>(kdf: &mut K, context: Option<&[u8]>) {
+    if let Some(context) = context {
+        kdf.update(&(context.len() as u64).to_be_bytes());
+        kdf.update(context);
+    }
+}
+
+/// The universal combiner used by the GU and KU frameworks.  This is exposed so that component
+/// secrets computed outside of this crate (e.g., in an HSM) can be combined exactly as the
+/// frameworks do.  Pass `None` as the context to match the specified frameworks.
+#[allow(clippy::too_many_arguments)]
+pub fn universal_combiner<K: Kdf>(
     ss_pq: &[u8],
     ss_t: &[u8],
     ct_pq: &[u8],
@@ -130,36 +145,77 @@ fn universal_combiner<K: Kdf>(
     ek_pq: &[u8],
     ek_t: &[u8],
     label: &[u8],
+    context: Option<&[u8]>,
 ) -> Output {
-    K::compute(
-        ss_pq
-            .iter()
-            .chain(ss_t.iter())
-            .chain(ct_pq.iter())
-            .chain(ct_t.iter())
-            .chain(ek_pq.iter())
-            .chain(ek_t.iter())
-            .chain(label.iter())
-            .cloned(),
-    )
-}
-
-fn c2pri_combiner<K: Kdf>(
+    let mut output = vec![0; K::OUTPUT_SIZE];
+    universal_combiner_into::<K>(
+        ss_pq,
+        ss_t,
+        ct_pq,
+        ct_t,
+        ek_pq,
+        ek_t,
+        label,
+        context,
+        &mut output,
+    );
+    output
+}
+
+/// `universal_combiner` without allocating, writing into `output`, which must be
+/// `K::OUTPUT_SIZE` bytes long
+#[allow(clippy::too_many_arguments)]
+pub fn universal_combiner_into<K: Kdf>(
+    ss_pq: &[u8],
+    ss_t: &[u8],
+    ct_pq: &[u8],
+    ct_t: &[u8],
+    ek_pq: &[u8],
+    ek_t: &[u8],
+    label: &[u8],
+    context: Option<&[u8]>,
+    output: &mut [u8],
+) {
+    let mut kdf = K::new();
+    for part in [ss_pq, ss_t, ct_pq, ct_t, ek_pq, ek_t, label] {
+        kdf.update(part);
+    }
+    update_context(&mut kdf, context);
+    kdf.finalize_into(output);
+}
+
+/// The C2PRI combiner used by the GC and KC frameworks.  As with `universal_combiner`, pass
+/// `None` as the context to match the specified frameworks.
+pub fn c2pri_combiner<K: Kdf>(
     ss_pq: &[u8],
     ss_t: &[u8],
     ct_t: &[u8],
     ek_t: &[u8],
     label: &[u8],
+    context: Option<&[u8]>,
 ) -> Output {
-    K::compute(
-        ss_pq
-            .iter()
-            .chain(ss_t.iter())
-            .chain(ct_t.iter())
-            .chain(ek_t.iter())
-            .chain(label.iter())
-            .cloned(),
-    )
+    let mut output = vec![0; K::OUTPUT_SIZE];
+    c2pri_combiner_into::<K>(ss_pq, ss_t, ct_t, ek_t, label, context, &mut output);
+    output
+}
+
+/// `c2pri_combiner` without allocating, writing into `output`, which must be `K::OUTPUT_SIZE`
+/// bytes long
+pub fn c2pri_combiner_into<K: Kdf>(
+    ss_pq: &[u8],
+    ss_t: &[u8],
+    ct_t: &[u8],
+    ek_t: &[u8],
+    label: &[u8],
+    context: Option<&[u8]>,
+    output: &mut [u8],
+) {
+    let mut kdf = K::new();
+    for part in [ss_pq, ss_t, ct_t, ek_t, label] {
+        kdf.update(part);
+    }
+    update_context(&mut kdf, context);
+    kdf.finalize_into(output);
 }
 
 pub trait HybridKemConstants: SeedSize + SharedSecretSize {
@@ -172,9 +228,81 @@ pub struct HybridSubKeys {
     pub dk_t: Vec<u8>,
 }
 
-pub trait HybridKem: Kem<KeyInfo = HybridSubKeys> + EncapsDerand {}
+/// Hybrid KEMs that can check encapsulation keys and ciphertexts from untrusted sources.
+/// `encaps` and `decaps` only check lengths, and panic if the traditional component is not a
+/// group element.
+pub trait InputValidation: Kem {
+    /// Whether `ek` has the right length and its traditional component is a group element
+    fn validate_encapsulation_key(ek: &[u8]) -> bool;
+
+    /// Whether `ct` has the right length and its traditional component is a group element
+    fn validate_ciphertext(ct: &[u8]) -> bool;
+}
+
+pub trait HybridKem: Kem<KeyInfo = HybridSubKeys> + EncapsDerand + InputValidation {}
+
+impl<K> HybridKem for K where K: Kem<KeyInfo = HybridSubKeys> + EncapsDerand + InputValidation {}
+
+/// Hybrid KEMs that can bind an application context (e.g., a transcript hash or protocol
+/// identifier) into the shared secret.  The context is appended to the KDF input after the label,
+/// prefixed with its length.  An empty context is therefore distinct from no context; the `Kem`
+/// methods are the no-context variants.
+pub trait KemWithContext: Kem {
+    fn encaps_with_context(
+        ek: &EncapsulationKey,
+        context: &[u8],
+        rng: &mut impl CryptoRng,
+    ) -> (SharedSecret, Ciphertext);
+    fn decaps_with_context(dk: &DecapsulationKey, ct: &Ciphertext, context: &[u8]) -> SharedSecret;
+}
+
+/// The component values passed to `Combiner::combine` do not have the lengths of the instance
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CombinerError {
+    /// The named input has the wrong length
+    Length(&'static str),
+}
+
+fn check_lengths(inputs: [(&'static str, &[u8], usize); 6]) -> Result<(), CombinerError> {
+    match inputs.iter().find(|(_, value, size)| value.len() != *size) {
+        Some((name, _, _)) => Err(CombinerError::Length(name)),
+        None => Ok(()),
+    }
+}
 
-impl<K> HybridKem for K where K: Kem<KeyInfo = HybridSubKeys> + EncapsDerand {}
+/// Hybrid KEM instances whose combiner can be applied to externally computed component values.
+/// The KDF and label are those of the instance, so `combine` on the outputs of the component
+/// operations equals `Kem::decaps` on the corresponding hybrid ciphertext.
+pub trait Combiner: Kem {
+    fn combine(
+        ss_pq: &[u8],
+        ss_t: &[u8],
+        ct_pq: &[u8],
+        ct_t: &[u8],
+        ek_pq: &[u8],
+        ek_t: &[u8],
+    ) -> Result<SharedSecret, CombinerError> {
+        Self::combine_with_context(ss_pq, ss_t, ct_pq, ct_t, ek_pq, ek_t, None)
+    }
+
+    fn combine_with_context(
+        ss_pq: &[u8],
+        ss_t: &[u8],
+        ct_pq: &[u8],
+        ct_t: &[u8],
+        ek_pq: &[u8],
+        ek_t: &[u8],
+        context: Option<&[u8]>,
+    ) -> Result<SharedSecret, CombinerError>;
+}
+
+pub trait EncapsDerandWithContext: KemWithContext + EncapsDerand {
+    fn encaps_derand_with_context(
+        ek: &EncapsulationKey,
+        context: &[u8],
+        randomness: &[u8],
+    ) -> (Ciphertext, SharedSecret);
+}
 
 #[derive(Default)]
 pub struct GU<PQ, T, P, K, C> {
@@ -195,6 +323,60 @@ where
     const SHARED_SECRET_SIZE: usize = C::SHARED_SECRET_SIZE;
 }
 
+impl<PQ, T, P, K, C> GU<PQ, T, P, K, C>
+where
+    PQ: PqKem,
+    T: NominalGroup,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    fn encaps_inner(
+        ek: &EncapsulationKey,
+        context: Option<&[u8]>,
+        rng: &mut impl CryptoRng,
+    ) -> (SharedSecret, Ciphertext) {
+        assert_eq!(ek.len(), Self::ENCAPSULATION_KEY_SIZE);
+        let (ek_pq, ek_t) = split(ek, PQ::ENCAPSULATION_KEY_SIZE, T::ELEMENT_SIZE);
+        let (ss_pq, ss_t, ct_pq, ct_t) = prepare_encaps_group::<PQ, T>(&ek_pq, &ek_t, rng);
+        let ss_h = universal_combiner::<K>(
+            &ss_pq,
+            &ss_t,
+            &ct_pq,
+            &ct_t,
+            &ek_pq,
+            &ek_t,
+            C::LABEL,
+            context,
+        );
+        let mut ct_h = ct_pq;
+        ct_h.append(&mut ct_t.clone());
+        (ss_h, ct_h)
+    }
+
+    fn decaps_inner(
+        dk: &DecapsulationKey,
+        ct: &Ciphertext,
+        context: Option<&[u8]>,
+    ) -> SharedSecret {
+        assert_eq!(dk.len(), Self::DECAPSULATION_KEY_SIZE);
+        assert_eq!(ct.len(), Self::CIPHERTEXT_SIZE);
+        let (ct_pq, ct_t) = split(ct, PQ::CIPHERTEXT_SIZE, T::ELEMENT_SIZE);
+        let (dk_pq, dk_t, ek_pq, ek_t) = expand_decaps_key_group::<PQ, T, P>(dk);
+        let (ss_pq, ss_t) = prepare_decaps_group::<PQ, T>(&ct_pq, &ct_t, &dk_pq, &dk_t);
+        universal_combiner::<K>(
+            &ss_pq,
+            &ss_t,
+            &ct_pq,
+            &ct_t,
+            &ek_pq,
+            &ek_t,
+            C::LABEL,
+            context,
+        )
+    }
+}
+
 impl<PQ, T, P, K, C> Kem for GU<PQ, T, P, K, C>
 where
     PQ: PqKem,
@@ -211,51 +393,109 @@ where
 
     fn derive_key_pair(seed: &[u8]) -> (DecapsulationKey, EncapsulationKey, Self::KeyInfo) {
         assert_eq!(seed.len(), Self::SEED_SIZE);
-        let (dk_pq, dk_t, ek_pq, ek_t) = expand_decaps_key_group::<PQ, T, P>(&seed);
+        let (dk_pq, dk_t, ek_pq, ek_t) = expand_decaps_key_group::<PQ, T, P>(seed);
         let mut ek = ek_pq;
         ek.append(&mut ek_t.clone());
         (seed.to_vec(), ek, HybridSubKeys { dk_pq, dk_t })
     }
 
     fn encaps(ek: &EncapsulationKey, rng: &mut impl CryptoRng) -> (SharedSecret, Ciphertext) {
-        assert_eq!(ek.len(), Self::ENCAPSULATION_KEY_SIZE);
-        let (ek_pq, ek_t) = split(ek, PQ::ENCAPSULATION_KEY_SIZE, T::ELEMENT_SIZE);
-        let (ss_pq, ss_t, ct_pq, ct_t) = prepare_encaps_group::<PQ, T>(&ek_pq, &ek_t, rng);
-        let ss_h = universal_combiner::<K>(&ss_pq, &ss_t, &ct_pq, &ct_t, &ek_pq, &ek_t, C::LABEL);
-        let mut ct_h = ct_pq;
-        ct_h.append(&mut ct_t.clone());
-        (ss_h, ct_h)
+        Self::encaps_inner(ek, None, rng)
     }
 
     fn decaps(dk: &DecapsulationKey, ct: &Ciphertext) -> SharedSecret {
-        assert_eq!(dk.len(), Self::DECAPSULATION_KEY_SIZE);
-        assert_eq!(ct.len(), Self::CIPHERTEXT_SIZE);
-        let (ct_pq, ct_t) = split(ct, PQ::CIPHERTEXT_SIZE, T::ELEMENT_SIZE);
-        let (dk_pq, dk_t, ek_pq, ek_t) = expand_decaps_key_group::<PQ, T, P>(dk);
-        let (ss_pq, ss_t) = prepare_decaps_group::<PQ, T>(&ct_pq, &ct_t, &dk_pq, &dk_t);
-        let ss_h = universal_combiner::<K>(&ss_pq, &ss_t, &ct_pq, &ct_t, &ek_pq, &ek_t, C::LABEL);
-        ss_h
+        Self::decaps_inner(dk, ct, None)
     }
 }
 
-impl<PQ, T, P, K, C> EncapsDerand for GU<PQ, T, P, K, C>
+impl<PQ, T, P, K, C> Combiner for GU<PQ, T, P, K, C>
 where
-    PQ: PqKem + EncapsDerand,
+    PQ: PqKem,
     T: NominalGroup,
     P: Prg,
     K: Kdf,
     C: HybridKemConstants,
 {
-    const RANDOMNESS_SIZE: usize = PQ::RANDOMNESS_SIZE + T::SEED_SIZE;
+    fn combine_with_context(
+        ss_pq: &[u8],
+        ss_t: &[u8],
+        ct_pq: &[u8],
+        ct_t: &[u8],
+        ek_pq: &[u8],
+        ek_t: &[u8],
+        context: Option<&[u8]>,
+    ) -> Result<SharedSecret, CombinerError> {
+        check_lengths([
+            ("ss_pq", ss_pq, PQ::SHARED_SECRET_SIZE),
+            ("ss_t", ss_t, T::SHARED_SECRET_SIZE),
+            ("ct_pq", ct_pq, PQ::CIPHERTEXT_SIZE),
+            ("ct_t", ct_t, T::ELEMENT_SIZE),
+            ("ek_pq", ek_pq, PQ::ENCAPSULATION_KEY_SIZE),
+            ("ek_t", ek_t, T::ELEMENT_SIZE),
+        ])?;
+        Ok(universal_combiner::<K>(
+            ss_pq,
+            ss_t,
+            ct_pq,
+            ct_t,
+            ek_pq,
+            ek_t,
+            C::LABEL,
+            context,
+        ))
+    }
+}
 
-    fn encaps_derand(ek: &EncapsulationKey, randomness: &[u8]) -> (Ciphertext, SharedSecret) {
+impl<PQ, T, P, K, C> KemWithContext for GU<PQ, T, P, K, C>
+where
+    PQ: PqKem,
+    T: NominalGroup,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    fn encaps_with_context(
+        ek: &EncapsulationKey,
+        context: &[u8],
+        rng: &mut impl CryptoRng,
+    ) -> (SharedSecret, Ciphertext) {
+        Self::encaps_inner(ek, Some(context), rng)
+    }
+
+    fn decaps_with_context(dk: &DecapsulationKey, ct: &Ciphertext, context: &[u8]) -> SharedSecret {
+        Self::decaps_inner(dk, ct, Some(context))
+    }
+}
+
+impl<PQ, T, P, K, C> GU<PQ, T, P, K, C>
+where
+    PQ: PqKem + EncapsDerand,
+    T: NominalGroup,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    fn encaps_derand_inner(
+        ek: &EncapsulationKey,
+        context: Option<&[u8]>,
+        randomness: &[u8],
+    ) -> (Ciphertext, SharedSecret) {
         assert_eq!(ek.len(), Self::ENCAPSULATION_KEY_SIZE);
         assert_eq!(randomness.len(), Self::RANDOMNESS_SIZE);
 
         let (ek_pq, ek_t) = split(ek, PQ::ENCAPSULATION_KEY_SIZE, T::ELEMENT_SIZE);
         let (ss_pq, ss_t, ct_pq, ct_t) =
             prepare_encaps_group_derand::<PQ, T>(&ek_pq, &ek_t, randomness);
-        let ss_h = universal_combiner::<K>(&ss_pq, &ss_t, &ct_pq, &ct_t, &ek_pq, &ek_t, C::LABEL);
+        let ss_h = universal_combiner::<K>(
+            &ss_pq,
+            &ss_t,
+            &ct_pq,
+            &ct_t,
+            &ek_pq,
+            &ek_t,
+            C::LABEL,
+            context,
+        );
 
         let mut ct_h = ct_pq;
         ct_h.append(&mut ct_t.clone());
@@ -264,6 +504,38 @@ where
     }
 }
 
+impl<PQ, T, P, K, C> EncapsDerand for GU<PQ, T, P, K, C>
+where
+    PQ: PqKem + EncapsDerand,
+    T: NominalGroup,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    const RANDOMNESS_SIZE: usize = PQ::RANDOMNESS_SIZE + T::SEED_SIZE;
+
+    fn encaps_derand(ek: &EncapsulationKey, randomness: &[u8]) -> (Ciphertext, SharedSecret) {
+        Self::encaps_derand_inner(ek, None, randomness)
+    }
+}
+
+impl<PQ, T, P, K, C> EncapsDerandWithContext for GU<PQ, T, P, K, C>
+where
+    PQ: PqKem + EncapsDerand,
+    T: NominalGroup,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    fn encaps_derand_with_context(
+        ek: &EncapsulationKey,
+        context: &[u8],
+        randomness: &[u8],
+    ) -> (Ciphertext, SharedSecret) {
+        Self::encaps_derand_inner(ek, Some(context), randomness)
+    }
+}
+
 #[derive(Default)]
 pub struct GC<PQ, T, P, K, C> {
     _phantom: core::marker::PhantomData<(PQ, T, P, K, C)>,
@@ -283,6 +555,42 @@ where
     const SHARED_SECRET_SIZE: usize = C::SHARED_SECRET_SIZE;
 }
 
+impl<PQ, T, P, K, C> GC<PQ, T, P, K, C>
+where
+    PQ: PqKem,
+    T: NominalGroup,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    fn encaps_inner(
+        ek: &EncapsulationKey,
+        context: Option<&[u8]>,
+        rng: &mut impl CryptoRng,
+    ) -> (SharedSecret, Ciphertext) {
+        assert_eq!(ek.len(), Self::ENCAPSULATION_KEY_SIZE);
+        let (ek_pq, ek_t) = split(ek, PQ::ENCAPSULATION_KEY_SIZE, T::ELEMENT_SIZE);
+        let (ss_pq, ss_t, ct_pq, ct_t) = prepare_encaps_group::<PQ, T>(&ek_pq, &ek_t, rng);
+        let ss_h = c2pri_combiner::<K>(&ss_pq, &ss_t, &ct_t, &ek_t, C::LABEL, context);
+        let mut ct_h = ct_pq;
+        ct_h.append(&mut ct_t.clone());
+        (ss_h, ct_h)
+    }
+
+    fn decaps_inner(
+        dk: &DecapsulationKey,
+        ct: &Ciphertext,
+        context: Option<&[u8]>,
+    ) -> SharedSecret {
+        assert_eq!(dk.len(), Self::DECAPSULATION_KEY_SIZE);
+        assert_eq!(ct.len(), Self::CIPHERTEXT_SIZE);
+        let (ct_pq, ct_t) = split(ct, PQ::CIPHERTEXT_SIZE, T::ELEMENT_SIZE);
+        let (dk_pq, dk_t, _ek_pq, ek_t) = expand_decaps_key_group::<PQ, T, P>(dk);
+        let (ss_pq, ss_t) = prepare_decaps_group::<PQ, T>(&ct_pq, &ct_t, &dk_pq, &dk_t);
+        c2pri_combiner::<K>(&ss_pq, &ss_t, &ct_t, &ek_t, C::LABEL, context)
+    }
+}
+
 impl<PQ, T, P, K, C> Kem for GC<PQ, T, P, K, C>
 where
     PQ: PqKem,
@@ -299,51 +607,98 @@ where
 
     fn derive_key_pair(seed: &[u8]) -> (DecapsulationKey, EncapsulationKey, Self::KeyInfo) {
         assert_eq!(seed.len(), Self::SEED_SIZE);
-        let (dk_pq, dk_t, ek_pq, ek_t) = expand_decaps_key_group::<PQ, T, P>(&seed);
+        let (dk_pq, dk_t, ek_pq, ek_t) = expand_decaps_key_group::<PQ, T, P>(seed);
         let mut ek = ek_pq;
         ek.append(&mut ek_t.clone());
         (seed.to_vec(), ek, HybridSubKeys { dk_pq, dk_t })
     }
 
     fn encaps(ek: &EncapsulationKey, rng: &mut impl CryptoRng) -> (SharedSecret, Ciphertext) {
-        assert_eq!(ek.len(), Self::ENCAPSULATION_KEY_SIZE);
-        let (ek_pq, ek_t) = split(ek, PQ::ENCAPSULATION_KEY_SIZE, T::ELEMENT_SIZE);
-        let (ss_pq, ss_t, ct_pq, ct_t) = prepare_encaps_group::<PQ, T>(&ek_pq, &ek_t, rng);
-        let ss_h = c2pri_combiner::<K>(&ss_pq, &ss_t, &ct_t, &ek_t, C::LABEL);
-        let mut ct_h = ct_pq;
-        ct_h.append(&mut ct_t.clone());
-        (ss_h, ct_h)
+        Self::encaps_inner(ek, None, rng)
     }
 
     fn decaps(dk: &DecapsulationKey, ct: &Ciphertext) -> SharedSecret {
-        assert_eq!(dk.len(), Self::DECAPSULATION_KEY_SIZE);
-        assert_eq!(ct.len(), Self::CIPHERTEXT_SIZE);
-        let (ct_pq, ct_t) = split(ct, PQ::CIPHERTEXT_SIZE, T::ELEMENT_SIZE);
-        let (dk_pq, dk_t, _ek_pq, ek_t) = expand_decaps_key_group::<PQ, T, P>(dk);
-        let (ss_pq, ss_t) = prepare_decaps_group::<PQ, T>(&ct_pq, &ct_t, &dk_pq, &dk_t);
-        let ss_h = c2pri_combiner::<K>(&ss_pq, &ss_t, &ct_t, &ek_t, C::LABEL);
-        ss_h
+        Self::decaps_inner(dk, ct, None)
     }
 }
 
-impl<PQ, T, P, K, C> EncapsDerand for GC<PQ, T, P, K, C>
+impl<PQ, T, P, K, C> Combiner for GC<PQ, T, P, K, C>
 where
-    PQ: PqKem + EncapsDerand,
+    PQ: PqKem,
     T: NominalGroup,
     P: Prg,
     K: Kdf,
     C: HybridKemConstants,
 {
-    const RANDOMNESS_SIZE: usize = PQ::RANDOMNESS_SIZE + T::SEED_SIZE;
+    fn combine_with_context(
+        ss_pq: &[u8],
+        ss_t: &[u8],
+        ct_pq: &[u8],
+        ct_t: &[u8],
+        ek_pq: &[u8],
+        ek_t: &[u8],
+        context: Option<&[u8]>,
+    ) -> Result<SharedSecret, CombinerError> {
+        check_lengths([
+            ("ss_pq", ss_pq, PQ::SHARED_SECRET_SIZE),
+            ("ss_t", ss_t, T::SHARED_SECRET_SIZE),
+            ("ct_pq", ct_pq, PQ::CIPHERTEXT_SIZE),
+            ("ct_t", ct_t, T::ELEMENT_SIZE),
+            ("ek_pq", ek_pq, PQ::ENCAPSULATION_KEY_SIZE),
+            ("ek_t", ek_t, T::ELEMENT_SIZE),
+        ])?;
+        Ok(c2pri_combiner::<K>(
+            ss_pq,
+            ss_t,
+            ct_t,
+            ek_t,
+            C::LABEL,
+            context,
+        ))
+    }
+}
 
-    fn encaps_derand(ek: &EncapsulationKey, randomness: &[u8]) -> (Ciphertext, SharedSecret) {
+impl<PQ, T, P, K, C> KemWithContext for GC<PQ, T, P, K, C>
+where
+    PQ: PqKem,
+    T: NominalGroup,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    fn encaps_with_context(
+        ek: &EncapsulationKey,
+        context: &[u8],
+        rng: &mut impl CryptoRng,
+    ) -> (SharedSecret, Ciphertext) {
+        Self::encaps_inner(ek, Some(context), rng)
+    }
+
+    fn decaps_with_context(dk: &DecapsulationKey, ct: &Ciphertext, context: &[u8]) -> SharedSecret {
+        Self::decaps_inner(dk, ct, Some(context))
+    }
+}
+
+impl<PQ, T, P, K, C> GC<PQ, T, P, K, C>
+where
+    PQ: PqKem + EncapsDerand,
+    T: NominalGroup,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    fn encaps_derand_inner(
+        ek: &EncapsulationKey,
+        context: Option<&[u8]>,
+        randomness: &[u8],
+    ) -> (Ciphertext, SharedSecret) {
         assert_eq!(ek.len(), Self::ENCAPSULATION_KEY_SIZE);
         assert_eq!(randomness.len(), Self::RANDOMNESS_SIZE);
 
         let (ek_pq, ek_t) = split(ek, PQ::ENCAPSULATION_KEY_SIZE, T::ELEMENT_SIZE);
         let (ss_pq, ss_t, ct_pq, ct_t) =
             prepare_encaps_group_derand::<PQ, T>(&ek_pq, &ek_t, randomness);
-        let ss_h = c2pri_combiner::<K>(&ss_pq, &ss_t, &ct_t, &ek_t, C::LABEL);
+        let ss_h = c2pri_combiner::<K>(&ss_pq, &ss_t, &ct_t, &ek_t, C::LABEL, context);
 
         let mut ct_h = ct_pq;
         ct_h.append(&mut ct_t.clone());
@@ -352,6 +707,64 @@ where
     }
 }
 
+impl<PQ, T, P, K, C> EncapsDerand for GC<PQ, T, P, K, C>
+where
+    PQ: PqKem + EncapsDerand,
+    T: NominalGroup,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    const RANDOMNESS_SIZE: usize = PQ::RANDOMNESS_SIZE + T::SEED_SIZE;
+
+    fn encaps_derand(ek: &EncapsulationKey, randomness: &[u8]) -> (Ciphertext, SharedSecret) {
+        Self::encaps_derand_inner(ek, None, randomness)
+    }
+}
+
+impl<PQ, T, P, K, C> EncapsDerandWithContext for GC<PQ, T, P, K, C>
+where
+    PQ: PqKem + EncapsDerand,
+    T: NominalGroup,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    fn encaps_derand_with_context(
+        ek: &EncapsulationKey,
+        context: &[u8],
+        randomness: &[u8],
+    ) -> (Ciphertext, SharedSecret) {
+        Self::encaps_derand_inner(ek, Some(context), randomness)
+    }
+}
+
+// In both group-based frameworks, the traditional component comes last
+macro_rules! impl_input_validation {
+    ($framework:ident) => {
+        impl<PQ, T, P, K, C> InputValidation for $framework<PQ, T, P, K, C>
+        where
+            PQ: PqKem,
+            T: NominalGroup,
+            P: Prg,
+            K: Kdf,
+            C: HybridKemConstants,
+        {
+            fn validate_encapsulation_key(ek: &[u8]) -> bool {
+                ek.len() == Self::ENCAPSULATION_KEY_SIZE
+                    && T::validate_element(&ek[PQ::ENCAPSULATION_KEY_SIZE..])
+            }
+
+            fn validate_ciphertext(ct: &[u8]) -> bool {
+                ct.len() == Self::CIPHERTEXT_SIZE && T::validate_element(&ct[PQ::CIPHERTEXT_SIZE..])
+            }
+        }
+    };
+}
+
+impl_input_validation!(GU);
+impl_input_validation!(GC);
+
 #[derive(Default)]
 pub struct KU<PQ, T, P, K, C> {
     _phantom: core::marker::PhantomData<(PQ, T, P, K, C)>,
@@ -371,6 +784,60 @@ where
     const SHARED_SECRET_SIZE: usize = C::SHARED_SECRET_SIZE;
 }
 
+impl<PQ, T, P, K, C> KU<PQ, T, P, K, C>
+where
+    PQ: PqKem,
+    T: TKem,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    fn encaps_inner(
+        ek: &EncapsulationKey,
+        context: Option<&[u8]>,
+        rng: &mut impl CryptoRng,
+    ) -> (SharedSecret, Ciphertext) {
+        assert_eq!(ek.len(), Self::ENCAPSULATION_KEY_SIZE);
+        let (ek_pq, ek_t) = split(ek, PQ::ENCAPSULATION_KEY_SIZE, T::ENCAPSULATION_KEY_SIZE);
+        let (ss_pq, ss_t, ct_pq, ct_t) = prepare_encaps_kem::<PQ, T>(&ek_pq, &ek_t, rng);
+        let ss_h = universal_combiner::<K>(
+            &ss_pq,
+            &ss_t,
+            &ct_pq,
+            &ct_t,
+            &ek_pq,
+            &ek_t,
+            C::LABEL,
+            context,
+        );
+        let mut ct_h = ct_pq;
+        ct_h.append(&mut ct_t.clone());
+        (ss_h, ct_h)
+    }
+
+    fn decaps_inner(
+        dk: &DecapsulationKey,
+        ct: &Ciphertext,
+        context: Option<&[u8]>,
+    ) -> SharedSecret {
+        assert_eq!(dk.len(), Self::DECAPSULATION_KEY_SIZE);
+        assert_eq!(ct.len(), Self::CIPHERTEXT_SIZE);
+        let (ct_pq, ct_t) = split(ct, PQ::CIPHERTEXT_SIZE, T::CIPHERTEXT_SIZE);
+        let (dk_pq, dk_t, ek_pq, ek_t) = expand_decaps_key_kem::<PQ, T, P>(dk);
+        let (ss_pq, ss_t) = prepare_decaps_kem::<PQ, T>(&ct_pq, &ct_t, &dk_pq, &dk_t);
+        universal_combiner::<K>(
+            &ss_pq,
+            &ss_t,
+            &ct_pq,
+            &ct_t,
+            &ek_pq,
+            &ek_t,
+            C::LABEL,
+            context,
+        )
+    }
+}
+
 impl<PQ, T, P, K, C> Kem for KU<PQ, T, P, K, C>
 where
     PQ: PqKem,
@@ -387,30 +854,77 @@ where
 
     fn derive_key_pair(seed: &[u8]) -> (DecapsulationKey, EncapsulationKey, Self::KeyInfo) {
         assert_eq!(seed.len(), Self::SEED_SIZE);
-        let (dk_pq, dk_t, ek_pq, ek_t) = expand_decaps_key_kem::<PQ, T, P>(&seed);
+        let (dk_pq, dk_t, ek_pq, ek_t) = expand_decaps_key_kem::<PQ, T, P>(seed);
         let mut ek = ek_pq;
         ek.append(&mut ek_t.clone());
         (seed.to_vec(), ek, HybridSubKeys { dk_pq, dk_t })
     }
 
     fn encaps(ek: &EncapsulationKey, rng: &mut impl CryptoRng) -> (SharedSecret, Ciphertext) {
-        assert_eq!(ek.len(), Self::ENCAPSULATION_KEY_SIZE);
-        let (ek_pq, ek_t) = split(ek, PQ::ENCAPSULATION_KEY_SIZE, T::ENCAPSULATION_KEY_SIZE);
-        let (ss_pq, ss_t, ct_pq, ct_t) = prepare_encaps_kem::<PQ, T>(&ek_pq, &ek_t, rng);
-        let ss_h = universal_combiner::<K>(&ss_pq, &ss_t, &ct_pq, &ct_t, &ek_pq, &ek_t, C::LABEL);
-        let mut ct_h = ct_pq;
-        ct_h.append(&mut ct_t.clone());
-        (ss_h, ct_h)
+        Self::encaps_inner(ek, None, rng)
     }
 
     fn decaps(dk: &DecapsulationKey, ct: &Ciphertext) -> SharedSecret {
-        assert_eq!(dk.len(), Self::DECAPSULATION_KEY_SIZE);
-        assert_eq!(ct.len(), Self::CIPHERTEXT_SIZE);
-        let (ct_pq, ct_t) = split(ct, PQ::CIPHERTEXT_SIZE, T::CIPHERTEXT_SIZE);
-        let (dk_pq, dk_t, ek_pq, ek_t) = expand_decaps_key_kem::<PQ, T, P>(dk);
-        let (ss_pq, ss_t) = prepare_decaps_kem::<PQ, T>(&ct_pq, &ct_t, &dk_pq, &dk_t);
-        let ss_h = universal_combiner::<K>(&ss_pq, &ss_t, &ct_pq, &ct_t, &ek_pq, &ek_t, C::LABEL);
-        ss_h
+        Self::decaps_inner(dk, ct, None)
+    }
+}
+
+impl<PQ, T, P, K, C> Combiner for KU<PQ, T, P, K, C>
+where
+    PQ: PqKem,
+    T: TKem,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    fn combine_with_context(
+        ss_pq: &[u8],
+        ss_t: &[u8],
+        ct_pq: &[u8],
+        ct_t: &[u8],
+        ek_pq: &[u8],
+        ek_t: &[u8],
+        context: Option<&[u8]>,
+    ) -> Result<SharedSecret, CombinerError> {
+        check_lengths([
+            ("ss_pq", ss_pq, PQ::SHARED_SECRET_SIZE),
+            ("ss_t", ss_t, T::SHARED_SECRET_SIZE),
+            ("ct_pq", ct_pq, PQ::CIPHERTEXT_SIZE),
+            ("ct_t", ct_t, T::CIPHERTEXT_SIZE),
+            ("ek_pq", ek_pq, PQ::ENCAPSULATION_KEY_SIZE),
+            ("ek_t", ek_t, T::ENCAPSULATION_KEY_SIZE),
+        ])?;
+        Ok(universal_combiner::<K>(
+            ss_pq,
+            ss_t,
+            ct_pq,
+            ct_t,
+            ek_pq,
+            ek_t,
+            C::LABEL,
+            context,
+        ))
+    }
+}
+
+impl<PQ, T, P, K, C> KemWithContext for KU<PQ, T, P, K, C>
+where
+    PQ: PqKem,
+    T: TKem,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    fn encaps_with_context(
+        ek: &EncapsulationKey,
+        context: &[u8],
+        rng: &mut impl CryptoRng,
+    ) -> (SharedSecret, Ciphertext) {
+        Self::encaps_inner(ek, Some(context), rng)
+    }
+
+    fn decaps_with_context(dk: &DecapsulationKey, ct: &Ciphertext, context: &[u8]) -> SharedSecret {
+        Self::decaps_inner(dk, ct, Some(context))
     }
 }
 
@@ -433,6 +947,42 @@ where
     const SHARED_SECRET_SIZE: usize = C::SHARED_SECRET_SIZE;
 }
 
+impl<PQ, T, P, K, C> KC<PQ, T, P, K, C>
+where
+    PQ: PqKem,
+    T: TKem,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    fn encaps_inner(
+        ek: &EncapsulationKey,
+        context: Option<&[u8]>,
+        rng: &mut impl CryptoRng,
+    ) -> (SharedSecret, Ciphertext) {
+        assert_eq!(ek.len(), Self::ENCAPSULATION_KEY_SIZE);
+        let (ek_pq, ek_t) = split(ek, PQ::ENCAPSULATION_KEY_SIZE, T::ENCAPSULATION_KEY_SIZE);
+        let (ss_pq, ss_t, ct_pq, ct_t) = prepare_encaps_kem::<PQ, T>(&ek_pq, &ek_t, rng);
+        let ss_h = c2pri_combiner::<K>(&ss_pq, &ss_t, &ct_t, &ek_t, C::LABEL, context);
+        let mut ct_h = ct_pq;
+        ct_h.append(&mut ct_t.clone());
+        (ss_h, ct_h)
+    }
+
+    fn decaps_inner(
+        dk: &DecapsulationKey,
+        ct: &Ciphertext,
+        context: Option<&[u8]>,
+    ) -> SharedSecret {
+        assert_eq!(dk.len(), Self::DECAPSULATION_KEY_SIZE);
+        assert_eq!(ct.len(), Self::CIPHERTEXT_SIZE);
+        let (ct_pq, ct_t) = split(ct, PQ::CIPHERTEXT_SIZE, T::CIPHERTEXT_SIZE);
+        let (dk_pq, dk_t, _ek_pq, ek_t) = expand_decaps_key_kem::<PQ, T, P>(dk);
+        let (ss_pq, ss_t) = prepare_decaps_kem::<PQ, T>(&ct_pq, &ct_t, &dk_pq, &dk_t);
+        c2pri_combiner::<K>(&ss_pq, &ss_t, &ct_t, &ek_t, C::LABEL, context)
+    }
+}
+
 impl<PQ, T, P, K, C> Kem for KC<PQ, T, P, K, C>
 where
     PQ: PqKem,
@@ -449,29 +999,514 @@ where
 
     fn derive_key_pair(seed: &[u8]) -> (DecapsulationKey, EncapsulationKey, Self::KeyInfo) {
         assert_eq!(seed.len(), Self::SEED_SIZE);
-        let (dk_pq, dk_t, ek_pq, ek_t) = expand_decaps_key_kem::<PQ, T, P>(&seed);
+        let (dk_pq, dk_t, ek_pq, ek_t) = expand_decaps_key_kem::<PQ, T, P>(seed);
         let mut ek = ek_pq;
         ek.append(&mut ek_t.clone());
         (seed.to_vec(), ek, HybridSubKeys { dk_pq, dk_t })
     }
 
     fn encaps(ek: &EncapsulationKey, rng: &mut impl CryptoRng) -> (SharedSecret, Ciphertext) {
-        assert_eq!(ek.len(), Self::ENCAPSULATION_KEY_SIZE);
-        let (ek_pq, ek_t) = split(ek, PQ::ENCAPSULATION_KEY_SIZE, T::ENCAPSULATION_KEY_SIZE);
-        let (ss_pq, ss_t, ct_pq, ct_t) = prepare_encaps_kem::<PQ, T>(&ek_pq, &ek_t, rng);
-        let ss_h = c2pri_combiner::<K>(&ss_pq, &ss_t, &ct_t, &ek_t, C::LABEL);
-        let mut ct_h = ct_pq;
-        ct_h.append(&mut ct_t.clone());
-        (ss_h, ct_h)
+        Self::encaps_inner(ek, None, rng)
     }
 
     fn decaps(dk: &DecapsulationKey, ct: &Ciphertext) -> SharedSecret {
-        assert_eq!(dk.len(), Self::DECAPSULATION_KEY_SIZE);
-        assert_eq!(ct.len(), Self::CIPHERTEXT_SIZE);
-        let (ct_pq, ct_t) = split(ct, PQ::CIPHERTEXT_SIZE, T::CIPHERTEXT_SIZE);
-        let (dk_pq, dk_t, _ek_pq, ek_t) = expand_decaps_key_kem::<PQ, T, P>(dk);
-        let (ss_pq, ss_t) = prepare_decaps_kem::<PQ, T>(&ct_pq, &ct_t, &dk_pq, &dk_t);
-        let ss_h = c2pri_combiner::<K>(&ss_pq, &ss_t, &ct_t, &ek_t, C::LABEL);
-        ss_h
+        Self::decaps_inner(dk, ct, None)
+    }
+}
+
+impl<PQ, T, P, K, C> Combiner for KC<PQ, T, P, K, C>
+where
+    PQ: PqKem,
+    T: TKem,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    fn combine_with_context(
+        ss_pq: &[u8],
+        ss_t: &[u8],
+        ct_pq: &[u8],
+        ct_t: &[u8],
+        ek_pq: &[u8],
+        ek_t: &[u8],
+        context: Option<&[u8]>,
+    ) -> Result<SharedSecret, CombinerError> {
+        check_lengths([
+            ("ss_pq", ss_pq, PQ::SHARED_SECRET_SIZE),
+            ("ss_t", ss_t, T::SHARED_SECRET_SIZE),
+            ("ct_pq", ct_pq, PQ::CIPHERTEXT_SIZE),
+            ("ct_t", ct_t, T::CIPHERTEXT_SIZE),
+            ("ek_pq", ek_pq, PQ::ENCAPSULATION_KEY_SIZE),
+            ("ek_t", ek_t, T::ENCAPSULATION_KEY_SIZE),
+        ])?;
+        Ok(c2pri_combiner::<K>(
+            ss_pq,
+            ss_t,
+            ct_t,
+            ek_t,
+            C::LABEL,
+            context,
+        ))
+    }
+}
+
+impl<PQ, T, P, K, C> KemWithContext for KC<PQ, T, P, K, C>
+where
+    PQ: PqKem,
+    T: TKem,
+    P: Prg,
+    K: Kdf,
+    C: HybridKemConstants,
+{
+    fn encaps_with_context(
+        ek: &EncapsulationKey,
+        context: &[u8],
+        rng: &mut impl CryptoRng,
+    ) -> (SharedSecret, Ciphertext) {
+        Self::encaps_inner(ek, Some(context), rng)
+    }
+
+    fn decaps_with_context(dk: &DecapsulationKey, ct: &Ciphertext, context: &[u8]) -> SharedSecret {
+        Self::decaps_inner(dk, ct, Some(context))
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::*;
+    use crate::kdf::Sha3_256;
+    use crate::prg::Shake256;
+
+    /// A traditional KEM built from a nominal group, so that the KEM-based frameworks can be
+    /// exercised without a concrete TKem instance.
+    pub struct DhKem<G> {
+        _phantom: core::marker::PhantomData<G>,
+    }
+
+    impl<G: SeedSize> SeedSize for DhKem<G> {
+        const SEED_SIZE: usize = G::SEED_SIZE;
+    }
+
+    impl<G: SharedSecretSize> SharedSecretSize for DhKem<G> {
+        const SHARED_SECRET_SIZE: usize = G::SHARED_SECRET_SIZE;
+    }
+
+    impl<G: NominalGroup> Kem for DhKem<G> {
+        const ENCAPSULATION_KEY_SIZE: usize = G::ELEMENT_SIZE;
+        const DECAPSULATION_KEY_SIZE: usize = G::SCALAR_SIZE;
+        const CIPHERTEXT_SIZE: usize = G::ELEMENT_SIZE;
+
+        type KeyInfo = ();
+
+        fn derive_key_pair(seed: &[u8]) -> (DecapsulationKey, EncapsulationKey, Self::KeyInfo) {
+            let dk = G::random_scalar(&seed.to_vec());
+            let ek = G::exp(&G::generator(), &dk);
+            (dk, ek, ())
+        }
+
+        fn encaps(ek: &EncapsulationKey, rng: &mut impl CryptoRng) -> (SharedSecret, Ciphertext) {
+            let mut seed_e = vec![0u8; G::SEED_SIZE];
+            rng.fill(seed_e.as_mut_slice());
+            let sk_e = G::random_scalar(&seed_e);
+            let ct = G::exp(&G::generator(), &sk_e);
+            let ss = G::element_to_shared_secret(&G::exp(ek, &sk_e));
+            (ss, ct)
+        }
+
+        fn decaps(dk: &DecapsulationKey, ct: &Ciphertext) -> SharedSecret {
+            G::element_to_shared_secret(&G::exp(ct, dk))
+        }
+    }
+
+    impl<G: NominalGroup> TKem for DhKem<G> {}
+
+    /// An encapsulation key for `K` whose traditional component is not a group element.  Only
+    /// meaningful for the NIST curve instances, where flipping the last byte of an encoded point
+    /// moves it off the curve.
+    pub fn off_curve_encapsulation_key<K: HybridKem>(ek: &EncapsulationKey) -> EncapsulationKey {
+        let mut ek = ek.clone();
+        *ek.last_mut().unwrap() ^= 0x01;
+        assert!(!K::validate_encapsulation_key(&ek));
+        ek
+    }
+
+    /// A ciphertext for `K` whose traditional component is not a group element, built the same
+    /// way as `off_curve_encapsulation_key`
+    pub fn off_curve_ciphertext<K: HybridKem>(ek: &EncapsulationKey) -> Ciphertext {
+        let (mut ct, _) = K::encaps_derand(ek, &vec![6u8; K::RANDOMNESS_SIZE]);
+        *ct.last_mut().unwrap() ^= 0x01;
+        assert!(!K::validate_ciphertext(&ct));
+        ct
+    }
+
+    pub struct TestConstants;
+
+    impl SeedSize for TestConstants {
+        const SEED_SIZE: usize = 32;
+    }
+
+    impl SharedSecretSize for TestConstants {
+        const SHARED_SECRET_SIZE: usize = 32;
+    }
+
+    impl HybridKemConstants for TestConstants {
+        const LABEL: &'static [u8] = b"test";
+    }
+
+    type TestGU = GU<MlKem768, X25519, Shake256, Sha3_256, TestConstants>;
+    type TestGC = GC<MlKem768, X25519, Shake256, Sha3_256, TestConstants>;
+    type TestKU = KU<MlKem768, DhKem<P256>, Shake256, Sha3_256, TestConstants>;
+    type TestKC = KC<MlKem768, DhKem<P256>, Shake256, Sha3_256, TestConstants>;
+
+    fn test_context<K: KemWithContext>() {
+        let mut rng = rand::rng();
+        let (dk, ek, _) = K::derive_key_pair(&vec![5u8; K::SEED_SIZE]);
+
+        let (ss, ct) = K::encaps_with_context(&ek, b"context", &mut rng);
+        assert_eq!(
+            K::decaps_with_context(&dk, &ct, b"context"),
+            ss,
+            "Decapsulation with the same context should produce the same shared secret"
+        );
+        assert_ne!(
+            K::decaps_with_context(&dk, &ct, b"other"),
+            ss,
+            "Different contexts should produce different shared secrets"
+        );
+        assert_ne!(
+            K::decaps(&dk, &ct),
+            ss,
+            "Omitting the context should produce a different shared secret"
+        );
+        assert_ne!(
+            K::decaps_with_context(&dk, &ct, b""),
+            K::decaps(&dk, &ct),
+            "An empty context should be distinct from no context"
+        );
+
+        let (ss, ct) = K::encaps(&ek, &mut rng);
+        assert_eq!(K::decaps(&dk, &ct), ss);
+    }
+
+    fn test_context_derand<K: EncapsDerandWithContext>() {
+        let (dk, ek, _) = K::derive_key_pair(&vec![6u8; K::SEED_SIZE]);
+        let randomness = vec![7u8; K::RANDOMNESS_SIZE];
+
+        let (ct1, ss1) = K::encaps_derand(&ek, &randomness);
+        let (ct2, ss2) = K::encaps_derand_with_context(&ek, b"context", &randomness);
+
+        assert_eq!(ct1, ct2, "The context should not affect the ciphertext");
+        assert_ne!(ss1, ss2, "The context should affect the shared secret");
+        assert_eq!(K::decaps(&dk, &ct1), ss1);
+        assert_eq!(K::decaps_with_context(&dk, &ct2, b"context"), ss2);
+    }
+
+    // The context follows the label, prefixed with its length as eight bytes
+    #[test]
+    fn context_encoding() {
+        let combine = |context| c2pri_combiner::<Sha3_256>(b"", b"", b"", b"", b"label", context);
+        assert_eq!(combine(None), Sha3_256::compute(&[b"label"]));
+        assert_eq!(combine(Some(b"")), Sha3_256::compute(&[b"label", &[0; 8]]));
+        assert_eq!(
+            combine(Some(b"ab")),
+            Sha3_256::compute(&[b"label", b"\0\0\0\0\0\0\0\x02ab"])
+        );
+    }
+
+    #[test]
+    fn gu_context() {
+        test_context::<TestGU>();
+        test_context_derand::<TestGU>();
+    }
+
+    #[test]
+    fn gc_context() {
+        test_context::<TestGC>();
+        test_context_derand::<TestGC>();
+    }
+
+    #[test]
+    fn ku_context() {
+        test_context::<TestKU>();
+    }
+
+    #[test]
+    fn kc_context() {
+        test_context::<TestKC>();
+    }
+
+    /// Recompute decapsulation from the component operations, as a caller holding the component
+    /// keys in external hardware would
+    fn test_combiner<PQ, T, H>()
+    where
+        PQ: PqKem,
+        T: NominalGroup,
+        H: HybridKem + Combiner + KemWithContext,
+    {
+        let (dk, ek, info) = H::derive_key_pair(&vec![8u8; H::SEED_SIZE]);
+        let (ct, ss) = H::encaps_derand(&ek, &vec![9u8; H::RANDOMNESS_SIZE]);
+
+        let (ek_pq, ek_t) = ek.split_at(PQ::ENCAPSULATION_KEY_SIZE);
+        let (ct_pq, ct_t) = ct.split_at(PQ::CIPHERTEXT_SIZE);
+        let ss_pq = PQ::decaps(&info.dk_pq, &ct_pq.to_vec());
+        let ss_t = T::element_to_shared_secret(&T::exp(&ct_t.to_vec(), &info.dk_t));
+
+        let combined = H::combine(&ss_pq, &ss_t, ct_pq, ct_t, ek_pq, ek_t).unwrap();
+        assert_eq!(combined, ss, "Combiner should match encapsulation");
+        assert_eq!(
+            combined,
+            H::decaps(&dk, &ct),
+            "Combiner should match decapsulation"
+        );
+
+        let combined =
+            H::combine_with_context(&ss_pq, &ss_t, ct_pq, ct_t, ek_pq, ek_t, Some(b"context"));
+        assert_eq!(combined, Ok(H::decaps_with_context(&dk, &ct, b"context")));
+
+        assert_eq!(
+            H::combine(&ss_pq, &ss_t, ct_pq, &ct_t[1..], ek_pq, ek_t),
+            Err(CombinerError::Length("ct_t"))
+        );
+        assert_eq!(
+            H::combine(&ss_pq, &ss_t, ct_pq, ct_t, ek_pq, &[]),
+            Err(CombinerError::Length("ek_t"))
+        );
+    }
+
+    #[test]
+    fn gu_combiner() {
+        test_combiner::<MlKem768, X25519, TestGU>();
+    }
+
+    #[test]
+    fn gc_combiner() {
+        use crate::{MlKem1024P384, MlKem768P256, MlKem768X25519};
+
+        test_combiner::<MlKem768, X25519, TestGC>();
+        test_combiner::<MlKem768, P256, MlKem768P256>();
+        test_combiner::<MlKem768, X25519, MlKem768X25519>();
+        test_combiner::<MlKem1024, P384, MlKem1024P384>();
+    }
+
+    #[test]
+    fn combiner_functions() {
+        use crate::{MlKem768X25519, MlKem768X25519Constants};
+
+        let (dk, ek, info) = MlKem768X25519::derive_key_pair(&[1u8; 32]);
+        let (ct, ss) = MlKem768X25519::encaps_derand(&ek, &[2u8; 64]);
+
+        let (ek_pq, ek_t) = ek.split_at(MlKem768::ENCAPSULATION_KEY_SIZE);
+        let (ct_pq, ct_t) = ct.split_at(MlKem768::CIPHERTEXT_SIZE);
+        let ss_pq = MlKem768::decaps(&info.dk_pq, &ct_pq.to_vec());
+        let ss_t = X25519::exp(&ct_t.to_vec(), &info.dk_t);
+
+        let label = MlKem768X25519Constants::LABEL;
+        let ss_c2pri = c2pri_combiner::<Sha3_256>(&ss_pq, &ss_t, ct_t, ek_t, label, None);
+        assert_eq!(ss_c2pri, ss);
+        assert_eq!(ss_c2pri, MlKem768X25519::decaps(&dk, &ct));
+
+        // The universal combiner additionally binds the PQ ciphertext and encapsulation key
+        let ss_universal =
+            universal_combiner::<Sha3_256>(&ss_pq, &ss_t, ct_pq, ct_t, ek_pq, ek_t, label, None);
+        assert_ne!(ss_universal, ss);
+    }
+
+    /// Property tests of the framework invariants over random seeds and randomness, for each
+    /// framework on combinations of components beyond the instances
+    mod properties {
+        use super::*;
+        use proptest::collection::vec;
+        use proptest::prelude::*;
+        use proptest::sample::Index;
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+
+        /// Each case derives keys and encapsulates several times, which is slow on the NIST
+        /// curves in debug builds, so those combinations run fewer cases than the proptest
+        /// default unless `PROPTEST_CASES` is set
+        fn config(slow: bool) -> ProptestConfig {
+            match std::env::var("PROPTEST_CASES") {
+                Err(_) if slow => ProptestConfig::with_cases(32),
+                _ => ProptestConfig::default(),
+            }
+        }
+
+        fn seed() -> impl Strategy<Value = Vec<u8>> {
+            vec(any::<u8>(), TestConstants::SEED_SIZE)
+        }
+
+        fn seed_determinism<H: Kem>(seed: &[u8]) -> Result<(), TestCaseError> {
+            let (dk, ek, _) = H::derive_key_pair(seed);
+            prop_assert_eq!(dk.len(), H::DECAPSULATION_KEY_SIZE);
+            prop_assert_eq!(ek.len(), H::ENCAPSULATION_KEY_SIZE);
+
+            let (dk2, ek2, _) = H::derive_key_pair(seed);
+            prop_assert_eq!(&dk, &dk2);
+            prop_assert_eq!(&ek, &ek2);
+
+            let mut other = seed.to_vec();
+            other[0] ^= 1;
+            prop_assert_ne!(H::derive_key_pair(&other).1, ek);
+            Ok(())
+        }
+
+        fn roundtrip<H: Kem>(seed: &[u8], rng_seed: [u8; 32]) -> Result<(), TestCaseError> {
+            let (dk, ek, _) = H::derive_key_pair(seed);
+            let (ss, ct) = H::encaps(&ek, &mut StdRng::from_seed(rng_seed));
+            prop_assert_eq!(ct.len(), H::CIPHERTEXT_SIZE);
+            prop_assert_eq!(ss.len(), H::SHARED_SECRET_SIZE);
+            prop_assert_eq!(H::decaps(&dk, &ct), ss);
+            Ok(())
+        }
+
+        fn roundtrip_derand<H: EncapsDerand>(
+            seed: &[u8],
+            randomness: &[u8],
+        ) -> Result<(), TestCaseError> {
+            let (dk, ek, _) = H::derive_key_pair(seed);
+            let (ct, ss) = H::encaps_derand(&ek, randomness);
+            prop_assert_eq!(H::encaps_derand(&ek, randomness), (ct.clone(), ss.clone()));
+            prop_assert_eq!(H::decaps(&dk, &ct), ss);
+            Ok(())
+        }
+
+        /// Flip the bits of `mask` in one byte of the ciphertext, which must change the shared
+        /// secret
+        fn tampered_ciphertext<PQ, G, H>(
+            seed: &[u8],
+            rng_seed: [u8; 32],
+            index: Index,
+            mask: u8,
+        ) -> Result<(), TestCaseError>
+        where
+            PQ: PqKem,
+            G: NominalGroup,
+            H: Kem,
+        {
+            let (dk, ek, _) = H::derive_key_pair(seed);
+            let (ss, mut ct) = H::encaps(&ek, &mut StdRng::from_seed(rng_seed));
+            let i = index.index(ct.len());
+            ct[i] ^= mask;
+
+            // On the NIST curves, almost every change to the point leaves the curve, and `decaps`
+            // panics on such a ciphertext
+            prop_assume!(G::validate_element(&ct[PQ::CIPHERTEXT_SIZE..]));
+            prop_assert_ne!(H::decaps(&dk, &ct), ss);
+            Ok(())
+        }
+
+        /// `U` and `C` are the universal and C2PRI frameworks on the same components.  They
+        /// share keys and ciphertexts, and their shared secrets differ exactly in that `C`
+        /// leaves `ct_pq` and `ek_pq` out of the combiner.
+        fn universal_vs_c2pri<PQ, G, U, C>(
+            seed: &[u8],
+            rng_seed: [u8; 32],
+        ) -> Result<(), TestCaseError>
+        where
+            PQ: PqKem,
+            G: NominalGroup,
+            U: Kem<KeyInfo = HybridSubKeys>,
+            C: Kem<KeyInfo = HybridSubKeys>,
+        {
+            let (dk, ek, info) = U::derive_key_pair(seed);
+            let (dk_c, ek_c, _) = C::derive_key_pair(seed);
+            prop_assert_eq!(&dk, &dk_c);
+            prop_assert_eq!(&ek, &ek_c);
+
+            let (ss_u, ct) = U::encaps(&ek, &mut StdRng::from_seed(rng_seed));
+            let (ss_c, ct_c) = C::encaps(&ek, &mut StdRng::from_seed(rng_seed));
+            prop_assert_eq!(&ct, &ct_c);
+            prop_assert_eq!(U::decaps(&dk, &ct), ss_u.clone());
+            prop_assert_eq!(C::decaps(&dk, &ct), ss_c.clone());
+
+            let (ek_pq, ek_t) = ek.split_at(PQ::ENCAPSULATION_KEY_SIZE);
+            let (ct_pq, ct_t) = ct.split_at(PQ::CIPHERTEXT_SIZE);
+            let ss_pq = PQ::decaps(&info.dk_pq, &ct_pq.to_vec());
+            let ss_t = DhKem::<G>::decaps(&info.dk_t, &ct_t.to_vec());
+
+            let label = TestConstants::LABEL;
+            let combine = |ct_pq: &[u8], ek_pq: &[u8]| {
+                universal_combiner::<Sha3_256>(&ss_pq, &ss_t, ct_pq, ct_t, ek_pq, ek_t, label, None)
+            };
+            prop_assert_eq!(&ss_u, &combine(ct_pq, ek_pq));
+            prop_assert_eq!(&ss_c, &combine(&[], &[]));
+            prop_assert_ne!(&ss_c, &combine(ct_pq, &[]));
+            prop_assert_ne!(&ss_c, &combine(&[], ek_pq));
+            Ok(())
+        }
+
+        macro_rules! properties {
+            ($name:ident, $pq:ty, $group:ty, $slow:expr) => {
+                mod $name {
+                    use super::*;
+
+                    type TestGU = GU<$pq, $group, Shake256, Sha3_256, TestConstants>;
+                    type TestGC = GC<$pq, $group, Shake256, Sha3_256, TestConstants>;
+                    type TestKU = KU<$pq, DhKem<$group>, Shake256, Sha3_256, TestConstants>;
+                    type TestKC = KC<$pq, DhKem<$group>, Shake256, Sha3_256, TestConstants>;
+
+                    proptest! {
+                        #![proptest_config(config($slow))]
+
+                        #[test]
+                        fn determinism(seed in seed()) {
+                            seed_determinism::<TestGU>(&seed)?;
+                            seed_determinism::<TestGC>(&seed)?;
+                            seed_determinism::<TestKU>(&seed)?;
+                            seed_determinism::<TestKC>(&seed)?;
+                        }
+
+                        #[test]
+                        fn encaps_decaps(seed in seed(), rng_seed in any::<[u8; 32]>()) {
+                            roundtrip::<TestGU>(&seed, rng_seed)?;
+                            roundtrip::<TestGC>(&seed, rng_seed)?;
+                            roundtrip::<TestKU>(&seed, rng_seed)?;
+                            roundtrip::<TestKC>(&seed, rng_seed)?;
+                        }
+
+                        #[test]
+                        fn encaps_derand_decaps(
+                            seed in seed(),
+                            randomness in vec(any::<u8>(), TestGU::RANDOMNESS_SIZE),
+                        ) {
+                            // KU and KC have no `EncapsDerand`, since `TKem` only encapsulates
+                            // with an RNG; `encaps_decaps` covers them
+                            roundtrip_derand::<TestGU>(&seed, &randomness)?;
+                            roundtrip_derand::<TestGC>(&seed, &randomness)?;
+                        }
+
+                        #[test]
+                        fn tampering(
+                            seed in seed(),
+                            rng_seed in any::<[u8; 32]>(),
+                            index in any::<Index>(),
+                            mask in 1u8..,
+                        ) {
+                            let (r, i, m) = (rng_seed, index, mask);
+                            tampered_ciphertext::<$pq, $group, TestGU>(&seed, r, i, m)?;
+                            tampered_ciphertext::<$pq, $group, TestGC>(&seed, r, i, m)?;
+                            tampered_ciphertext::<$pq, $group, TestKU>(&seed, r, i, m)?;
+                            tampered_ciphertext::<$pq, $group, TestKC>(&seed, r, i, m)?;
+                        }
+
+                        #[test]
+                        fn frameworks(seed in seed(), rng_seed in any::<[u8; 32]>()) {
+                            universal_vs_c2pri::<$pq, $group, TestGU, TestGC>(&seed, rng_seed)?;
+                            universal_vs_c2pri::<$pq, $group, TestKU, TestKC>(&seed, rng_seed)?;
+                        }
+                    }
+                }
+            };
+        }
+
+        properties!(mlkem512_p256, MlKem512, P256, true);
+        properties!(mlkem512_p384, MlKem512, P384, true);
+        properties!(mlkem512_x25519, MlKem512, X25519, false);
+        properties!(mlkem768_p256, MlKem768, P256, true);
+        properties!(mlkem768_p384, MlKem768, P384, true);
+        properties!(mlkem768_x25519, MlKem768, X25519, false);
+        properties!(mlkem1024_p256, MlKem1024, P256, true);
+        properties!(mlkem1024_p384, MlKem1024, P384, true);
+        properties!(mlkem1024_x25519, MlKem1024, X25519, false);
     }
 }
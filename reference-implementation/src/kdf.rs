@@ -1,25 +1,59 @@
+use alloc::vec;
+use alloc::vec::Vec;
 use sha3::Digest;
 
 pub type Output = Vec<u8>;
 
-pub trait Kdf {
+/// KDFs, computed incrementally over their input so that it never has to be concatenated
+pub trait Kdf: Sized {
     const OUTPUT_SIZE: usize;
 
-    fn compute(input: impl Iterator<Item = u8>) -> Output;
+    fn new() -> Self;
+    fn update(&mut self, input: &[u8]);
+
+    /// Write the output into `output`, which must be `OUTPUT_SIZE` bytes long
+    fn finalize_into(self, output: &mut [u8]);
+
+    fn finalize(self) -> Output {
+        let mut output = vec![0; Self::OUTPUT_SIZE];
+        self.finalize_into(&mut output);
+        output
+    }
+
+    /// Compute the KDF over the concatenation of `input`
+    fn compute(input: &[&[u8]]) -> Output {
+        let mut output = vec![0; Self::OUTPUT_SIZE];
+        Self::compute_into(input, &mut output);
+        output
+    }
+
+    /// Compute the KDF over the concatenation of `input` into `output`, without allocating
+    fn compute_into(input: &[&[u8]], output: &mut [u8]) {
+        let mut kdf = Self::new();
+        for part in input {
+            kdf.update(part);
+        }
+        kdf.finalize_into(output);
+    }
 }
 
 /// SHA3-256 based KDF
-pub struct Sha3_256;
+pub struct Sha3_256(sha3::Sha3_256);
 
 // Implementation of the bis::Kdf trait
 impl Kdf for Sha3_256 {
     const OUTPUT_SIZE: usize = 32;
 
-    fn compute(input: impl Iterator<Item = u8>) -> Output {
-        let input: Vec<u8> = input.collect();
-        let mut hasher = sha3::Sha3_256::new();
-        hasher.update(&input);
-        hasher.finalize().to_vec()
+    fn new() -> Self {
+        Self(sha3::Sha3_256::new())
+    }
+
+    fn update(&mut self, input: &[u8]) {
+        self.0.update(input);
+    }
+
+    fn finalize_into(self, output: &mut [u8]) {
+        output.copy_from_slice(&self.0.finalize());
     }
 }
 
@@ -34,25 +68,46 @@ mod test {
             .collect();
 
         // Test output length
-        let output = K::compute(input.iter().cloned());
+        let output = K::compute(&[&input]);
         assert_eq!(output.len(), K::OUTPUT_SIZE, "KDF output length mismatch");
 
         // Test determinism
-        let output2 = K::compute(input.iter().cloned());
+        let output2 = K::compute(&[&input]);
         assert_eq!(output, output2, "KDF should be deterministic");
 
         // Test different inputs produce different outputs
         let mut input2 = input.clone();
         input2[0] = input2[0].wrapping_add(1);
-        let output3 = K::compute(input2.iter().cloned());
+        let output3 = K::compute(&[&input2]);
         assert_ne!(
             output, output3,
             "Different inputs should produce different outputs"
         );
+
+        // Test that splitting the input does not change the output
+        let mut output4 = vec![0; K::OUTPUT_SIZE];
+        K::compute_into(&[&input[..10], &[], &input[10..]], &mut output4);
+        assert_eq!(output, output4, "compute_into should match compute");
+
+        let mut kdf = K::new();
+        for chunk in input.chunks(7) {
+            kdf.update(chunk);
+        }
+        assert_eq!(
+            output,
+            kdf.finalize(),
+            "Incremental updates should match compute"
+        );
     }
 
     #[test]
     fn sha3_256() {
         test_kdf_basic::<Sha3_256>();
+
+        // SHA3-256("abc")
+        assert_eq!(
+            Sha3_256::compute(&[b"a", b"bc"]),
+            hex_literal::hex!("3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532")
+        );
     }
 }
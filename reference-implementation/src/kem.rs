@@ -1,3 +1,5 @@
+use alloc::vec;
+use alloc::vec::Vec;
 use hybrid_array::typenum::Unsigned;
 use ml_kem::{
     kem::{Decapsulate, Encapsulate},
@@ -44,6 +46,14 @@ pub trait TKem: Kem {}
 /// Marker trait for post-quantum KEMs
 pub trait PqKem: Kem {}
 
+/// KEMs that can operate on caller-provided buffers without allocating.  The buffers must have
+/// exactly the sizes given by the `Kem` and `EncapsDerand` constants.
+pub trait HeaplessKem: EncapsDerand {
+    fn derive_key_pair_into(seed: &[u8], dk: &mut [u8], ek: &mut [u8]);
+    fn encaps_derand_into(ek: &[u8], randomness: &[u8], ct: &mut [u8], ss: &mut [u8]);
+    fn decaps_into(dk: &[u8], ct: &[u8], ss: &mut [u8]);
+}
+
 /// RNG wrapper to bridge between rand_core versions
 pub struct RngWrapper<'a, R: rand::CryptoRng>(pub &'a mut R);
 
@@ -99,15 +109,10 @@ macro_rules! define_ml_kem {
                 EncapsulationKey,
                 Self::KeyInfo,
             ) {
-                use ml_kem::$mlkem;
-
-                assert_eq!(seed.len(), Self::SEED_SIZE);
-                let d = ml_kem::B32::try_from(&seed[..32]).expect("Invalid seed slice");
-                let z = ml_kem::B32::try_from(&seed[32..]).expect("Invalid seed slice");
-                let (_dk_inner, ek_inner) = $mlkem::generate_deterministic(&d, &z);
-
-                let ek = ek_inner.as_bytes().as_slice().to_vec();
-                (seed.to_vec(), ek, ())
+                let mut dk = vec![0; Self::DECAPSULATION_KEY_SIZE];
+                let mut ek = vec![0; Self::ENCAPSULATION_KEY_SIZE];
+                Self::derive_key_pair_into(seed, &mut dk, &mut ek);
+                (dk, ek, ())
             }
 
             fn encaps(
@@ -131,20 +136,9 @@ macro_rules! define_ml_kem {
                 dk: &DecapsulationKey,
                 ct: &Ciphertext,
             ) -> SharedSecret {
-                use ml_kem::$mlkem;
-
-                assert_eq!(dk.len(), Self::DECAPSULATION_KEY_SIZE);
-                assert_eq!(ct.len(), Self::CIPHERTEXT_SIZE);
-                let d = ml_kem::B32::try_from(&dk[..32]).expect("Invalid DK slice");
-                let z = ml_kem::B32::try_from(&dk[32..]).expect("Invalid DK slice");
-                let (dk_inner, _ek_inner) = $mlkem::generate_deterministic(&d, &z);
-
-                let ct_inner = ml_kem::Ciphertext::<$mlkem>::try_from(ct.as_slice()).expect("Invalid CT");
-                let ss_inner = dk_inner
-                    .decapsulate(&ct_inner)
-                    .expect("Decapsulation failed");
-
-                ss_inner.as_slice().to_vec()
+                let mut ss = vec![0; Self::SHARED_SECRET_SIZE];
+                Self::decaps_into(dk, ct, &mut ss);
+                ss
             }
         }
 
@@ -157,6 +151,27 @@ macro_rules! define_ml_kem {
                 ek: &EncapsulationKey,
                 randomness: &[u8],
             ) -> (Ciphertext, SharedSecret) {
+                let mut ct = vec![0; Self::CIPHERTEXT_SIZE];
+                let mut ss = vec![0; Self::SHARED_SECRET_SIZE];
+                Self::encaps_derand_into(ek, randomness, &mut ct, &mut ss);
+                (ct, ss)
+            }
+        }
+
+        impl HeaplessKem for $mlkem {
+            fn derive_key_pair_into(seed: &[u8], dk: &mut [u8], ek: &mut [u8]) {
+                use ml_kem::$mlkem;
+
+                assert_eq!(seed.len(), Self::SEED_SIZE);
+                let d = ml_kem::B32::try_from(&seed[..32]).expect("Invalid seed slice");
+                let z = ml_kem::B32::try_from(&seed[32..]).expect("Invalid seed slice");
+                let (_dk_inner, ek_inner) = $mlkem::generate_deterministic(&d, &z);
+
+                dk.copy_from_slice(seed);
+                ek.copy_from_slice(ek_inner.as_bytes().as_slice());
+            }
+
+            fn encaps_derand_into(ek: &[u8], randomness: &[u8], ct: &mut [u8], ss: &mut [u8]) {
                 assert_eq!(
                     ek.len(),
                     <Self as Kem>::ENCAPSULATION_KEY_SIZE
@@ -167,16 +182,31 @@ macro_rules! define_ml_kem {
 
                 let ek_inner: ml_kem::kem::EncapsulationKey<$params> =
                     ml_kem::kem::EncapsulationKey::from_bytes(
-                        ek.as_slice().try_into().expect("Invalid EK size"),
+                        ek.try_into().expect("Invalid EK size"),
                     );
                 let (ct_inner, ss_inner) = ek_inner
                     .encapsulate_deterministic(&m)
                     .expect("Deterministic encapsulation failed");
 
-                let ct = ct_inner.as_slice().to_vec();
-                let ss = ss_inner.as_slice().to_vec();
+                ct.copy_from_slice(ct_inner.as_slice());
+                ss.copy_from_slice(ss_inner.as_slice());
+            }
 
-                (ct, ss)
+            fn decaps_into(dk: &[u8], ct: &[u8], ss: &mut [u8]) {
+                use ml_kem::$mlkem;
+
+                assert_eq!(dk.len(), Self::DECAPSULATION_KEY_SIZE);
+                assert_eq!(ct.len(), Self::CIPHERTEXT_SIZE);
+                let d = ml_kem::B32::try_from(&dk[..32]).expect("Invalid DK slice");
+                let z = ml_kem::B32::try_from(&dk[32..]).expect("Invalid DK slice");
+                let (dk_inner, _ek_inner) = $mlkem::generate_deterministic(&d, &z);
+
+                let ct_inner = ml_kem::Ciphertext::<$mlkem>::try_from(ct).expect("Invalid CT");
+                let ss_inner = dk_inner
+                    .decapsulate(&ct_inner)
+                    .expect("Decapsulation failed");
+
+                ss.copy_from_slice(ss_inner.as_slice());
             }
         }
     }
@@ -2,6 +2,13 @@
 //!
 //! This crate provides reference implementations of concrete hybrid Key Encapsulation
 //! Mechanisms (KEMs) as described in draft-irtf-cfrg-concrete-hybrid-kems.
+//!
+//! Without the default `std` feature, only the hybrid KEM frameworks, their components and the
+//! instances below are available, and they require nothing beyond `alloc`.
+
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
 
 /// Nominal groups
 pub mod group;
@@ -16,11 +23,55 @@ pub mod kdf;
 pub mod prg;
 
 /// Definition of test vector formats, generation, and validation
+#[cfg(feature = "std")]
 pub mod test_vectors;
 
+/// Randomized encapsulation conformance checks against other implementations
+#[cfg(feature = "std")]
+pub mod conformance;
+
+/// Differential testing against other implementations
+#[cfg(feature = "std")]
+pub mod differential;
+
 /// The hybrid KEM frameworks
 pub mod hybrid;
 
+/// Hybrid KEM operations on caller-provided buffers, without heap allocation
+pub mod heapless;
+
+/// Runtime selection of the hybrid KEM instances
+#[cfg(feature = "std")]
+pub mod algorithm;
+
+/// The registry of hybrid KEM labels
+#[cfg(feature = "std")]
+pub mod registry;
+
+/// Consistency checks between the draft and the code
+#[cfg(feature = "std")]
+pub mod draft;
+
+/// Decapsulation with component keys held in external key stores
+#[cfg(feature = "std")]
+pub mod oracle;
+
+/// TLS 1.3 hybrid key exchange groups
+#[cfg(feature = "std")]
+pub mod tls;
+
+/// Public-key encryption built on the hybrid KEMs
+#[cfg(feature = "std")]
+pub mod pke;
+
+/// Streaming encryption to multiple hybrid KEM recipients
+#[cfg(feature = "std")]
+pub mod stream;
+
+/// Statistical tests for secret-dependent timing
+#[cfg(feature = "std")]
+pub mod timing;
+
 // MLKEM768-P256
 pub struct MlKem768P256Constants;
 
@@ -94,4 +145,25 @@ mod test {
     fn mlkem1024_p384() {
         test_all::<MlKem1024P384>();
     }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn checked_in_vectors() {
+        use test_vectors::TestVectors;
+
+        let json = include_str!("../../test-vectors.json");
+        let test_vectors: TestVectors = serde_json::from_str(json).unwrap();
+
+        for v in &test_vectors.mlkem768_p256 {
+            assert!(v.verify::<MlKem768P256>().is_ok());
+        }
+
+        for v in &test_vectors.mlkem768_x25519 {
+            assert!(v.verify::<MlKem768X25519>().is_ok());
+        }
+
+        for v in &test_vectors.mlkem1024_p384 {
+            assert!(v.verify::<MlKem1024P384>().is_ok());
+        }
+    }
 }
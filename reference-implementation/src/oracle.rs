@@ -0,0 +1,385 @@
+//! Hybrid decapsulation with component keys held outside of the process
+//!
+//! The frameworks in `hybrid` re-derive `dk_pq` and `dk_t` from the hybrid seed on every
+//! decapsulation.  When key custody rules require the component keys to stay inside a key store
+//! (an HSM, a TPM, a PKCS#11 token), the caller can instead provide a `DecapsOracle` for the PQ
+//! KEM and a `DhOracle` for the nominal group, and the hybrid shared secret is computed from their
+//! outputs with the instance's combiner.
+
+use crate::group::{Element, NominalGroup, Scalar};
+use crate::hybrid::{Combiner, HybridKemConstants, InputValidation, GC, GU};
+use crate::kdf::Kdf;
+use crate::kem::{Ciphertext, DecapsulationKey, EncapsulationKey, PqKem, SharedSecret};
+use crate::prg::Prg;
+use std::collections::HashMap;
+
+/// An enumeration of the ways an oracle operation can fail
+#[derive(Debug, PartialEq, Eq)]
+pub enum OracleError {
+    /// The handle does not refer to a key in the key store
+    UnknownHandle,
+    /// The handle refers to a key of a different type
+    WrongKeyType,
+    /// An oracle returned a value whose length does not match the instance
+    Length,
+    /// The ciphertext has the wrong length, or its traditional component is not a group element
+    Ciphertext,
+}
+
+/// A PQ decapsulation key that can only be used through its decapsulation operation
+pub trait DecapsOracle {
+    fn encapsulation_key(&self) -> Result<EncapsulationKey, OracleError>;
+    fn decaps(&self, ct: &Ciphertext) -> Result<SharedSecret, OracleError>;
+}
+
+/// A traditional scalar that can only be used through Diffie-Hellman.  `dh` returns
+/// `ElementToSharedSecret(Exp(element, dk_t))`, which is what key stores typically expose (e.g.,
+/// the X coordinate returned by PKCS#11 ECDH derivation).
+pub trait DhOracle {
+    fn public_element(&self) -> Result<Element, OracleError>;
+    fn dh(&self, element: &Element) -> Result<SharedSecret, OracleError>;
+}
+
+/// Hybrid KEMs that can decapsulate using component oracles instead of the hybrid seed
+pub trait OracleDecaps: Combiner {
+    /// The hybrid encapsulation key corresponding to the keys behind the oracles
+    fn encapsulation_key_with_oracles(
+        pq: &impl DecapsOracle,
+        t: &impl DhOracle,
+    ) -> Result<EncapsulationKey, OracleError>;
+
+    fn decaps_with_oracles(
+        pq: &impl DecapsOracle,
+        t: &impl DhOracle,
+        ct: &Ciphertext,
+    ) -> Result<SharedSecret, OracleError>;
+}
+
+fn decaps_with_oracles<PQ: PqKem, H: Combiner + InputValidation>(
+    pq: &impl DecapsOracle,
+    t: &impl DhOracle,
+    ct: &Ciphertext,
+) -> Result<SharedSecret, OracleError> {
+    if !H::validate_ciphertext(ct) {
+        return Err(OracleError::Ciphertext);
+    }
+    let (ct_pq, ct_t) = ct.split_at(PQ::CIPHERTEXT_SIZE);
+    let ss_pq = pq.decaps(&ct_pq.to_vec())?;
+    let ss_t = t.dh(&ct_t.to_vec())?;
+    let ek_pq = pq.encapsulation_key()?;
+    let ek_t = t.public_element()?;
+    H::combine(&ss_pq, &ss_t, ct_pq, ct_t, &ek_pq, &ek_t).map_err(|_| OracleError::Length)
+}
+
+fn encapsulation_key_with_oracles(
+    pq: &impl DecapsOracle,
+    t: &impl DhOracle,
+) -> Result<EncapsulationKey, OracleError> {
+    let mut ek = pq.encapsulation_key()?;
+    ek.append(&mut t.public_element()?);
+    Ok(ek)
+}
+
+macro_rules! impl_oracle_decaps {
+    ($framework:ident) => {
+        impl<PQ, T, P, K, C> OracleDecaps for $framework<PQ, T, P, K, C>
+        where
+            PQ: PqKem,
+            T: NominalGroup,
+            P: Prg,
+            K: Kdf,
+            C: HybridKemConstants,
+        {
+            fn encapsulation_key_with_oracles(
+                pq: &impl DecapsOracle,
+                t: &impl DhOracle,
+            ) -> Result<EncapsulationKey, OracleError> {
+                encapsulation_key_with_oracles(pq, t)
+            }
+
+            fn decaps_with_oracles(
+                pq: &impl DecapsOracle,
+                t: &impl DhOracle,
+                ct: &Ciphertext,
+            ) -> Result<SharedSecret, OracleError> {
+                decaps_with_oracles::<PQ, Self>(pq, t, ct)
+            }
+        }
+    };
+}
+
+impl_oracle_decaps! { GU }
+impl_oracle_decaps! { GC }
+
+/// Reference backend that holds the PQ decapsulation key in memory
+pub struct SoftwareDecapsOracle<PQ> {
+    dk: DecapsulationKey,
+    ek: EncapsulationKey,
+    _phantom: core::marker::PhantomData<PQ>,
+}
+
+impl<PQ: PqKem> SoftwareDecapsOracle<PQ> {
+    pub fn new(dk: DecapsulationKey) -> Self {
+        let (dk, ek, _) = PQ::derive_key_pair(&dk);
+        Self {
+            dk,
+            ek,
+            _phantom: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<PQ: PqKem> DecapsOracle for SoftwareDecapsOracle<PQ> {
+    fn encapsulation_key(&self) -> Result<EncapsulationKey, OracleError> {
+        Ok(self.ek.clone())
+    }
+
+    fn decaps(&self, ct: &Ciphertext) -> Result<SharedSecret, OracleError> {
+        Ok(PQ::decaps(&self.dk, ct))
+    }
+}
+
+/// Reference backend that holds the traditional scalar in memory
+pub struct SoftwareDhOracle<T> {
+    dk: Scalar,
+    ek: Element,
+    _phantom: core::marker::PhantomData<T>,
+}
+
+impl<T: NominalGroup> SoftwareDhOracle<T> {
+    pub fn new(dk: Scalar) -> Self {
+        let ek = T::exp(&T::generator(), &dk);
+        Self {
+            dk,
+            ek,
+            _phantom: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: NominalGroup> DhOracle for SoftwareDhOracle<T> {
+    fn public_element(&self) -> Result<Element, OracleError> {
+        Ok(self.ek.clone())
+    }
+
+    fn dh(&self, element: &Element) -> Result<SharedSecret, OracleError> {
+        Ok(T::element_to_shared_secret(&T::exp(element, &self.dk)))
+    }
+}
+
+/// An opaque reference to a key held by a `Token`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle(u64);
+
+struct TokenKey {
+    // The component type the key was imported for, checked on every use
+    kind: &'static str,
+    private: Vec<u8>,
+    public: Vec<u8>,
+}
+
+/// A mock key store in the style of a PKCS#11 token.  Keys are imported once and afterwards are
+/// only reachable through handles; the token offers no way to export a private key.
+#[derive(Default)]
+pub struct Token {
+    keys: HashMap<Handle, TokenKey>,
+    next_handle: u64,
+}
+
+impl Token {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn insert(&mut self, kind: &'static str, private: Vec<u8>, public: Vec<u8>) -> Handle {
+        let handle = Handle(self.next_handle);
+        self.next_handle += 1;
+        self.keys.insert(
+            handle,
+            TokenKey {
+                kind,
+                private,
+                public,
+            },
+        );
+        handle
+    }
+
+    fn get(&self, handle: Handle, kind: &'static str) -> Result<&TokenKey, OracleError> {
+        let key = self.keys.get(&handle).ok_or(OracleError::UnknownHandle)?;
+        if key.kind != kind {
+            return Err(OracleError::WrongKeyType);
+        }
+        Ok(key)
+    }
+
+    /// Import a PQ decapsulation key
+    pub fn import_decaps_key<PQ: PqKem>(&mut self, dk: DecapsulationKey) -> Handle {
+        let (dk, ek, _) = PQ::derive_key_pair(&dk);
+        self.insert(std::any::type_name::<PQ>(), dk, ek)
+    }
+
+    /// Import a traditional scalar
+    pub fn import_scalar<T: NominalGroup>(&mut self, dk: Scalar) -> Handle {
+        let ek = T::exp(&T::generator(), &dk);
+        self.insert(std::any::type_name::<T>(), dk, ek)
+    }
+
+    /// Remove a key from the token
+    pub fn destroy(&mut self, handle: Handle) -> Result<(), OracleError> {
+        self.keys
+            .remove(&handle)
+            .map(|_| ())
+            .ok_or(OracleError::UnknownHandle)
+    }
+
+    /// A PQ decapsulation oracle for the key behind `handle`
+    pub fn decaps_oracle<PQ: PqKem>(&self, handle: Handle) -> TokenDecapsOracle<'_, PQ> {
+        TokenDecapsOracle {
+            token: self,
+            handle,
+            _phantom: core::marker::PhantomData,
+        }
+    }
+
+    /// A Diffie-Hellman oracle for the scalar behind `handle`
+    pub fn dh_oracle<T: NominalGroup>(&self, handle: Handle) -> TokenDhOracle<'_, T> {
+        TokenDhOracle {
+            token: self,
+            handle,
+            _phantom: core::marker::PhantomData,
+        }
+    }
+}
+
+pub struct TokenDecapsOracle<'a, PQ> {
+    token: &'a Token,
+    handle: Handle,
+    _phantom: core::marker::PhantomData<PQ>,
+}
+
+impl<PQ: PqKem> DecapsOracle for TokenDecapsOracle<'_, PQ> {
+    fn encapsulation_key(&self) -> Result<EncapsulationKey, OracleError> {
+        let key = self.token.get(self.handle, std::any::type_name::<PQ>())?;
+        Ok(key.public.clone())
+    }
+
+    fn decaps(&self, ct: &Ciphertext) -> Result<SharedSecret, OracleError> {
+        let key = self.token.get(self.handle, std::any::type_name::<PQ>())?;
+        Ok(PQ::decaps(&key.private, ct))
+    }
+}
+
+pub struct TokenDhOracle<'a, T> {
+    token: &'a Token,
+    handle: Handle,
+    _phantom: core::marker::PhantomData<T>,
+}
+
+impl<T: NominalGroup> DhOracle for TokenDhOracle<'_, T> {
+    fn public_element(&self) -> Result<Element, OracleError> {
+        let key = self.token.get(self.handle, std::any::type_name::<T>())?;
+        Ok(key.public.clone())
+    }
+
+    fn dh(&self, element: &Element) -> Result<SharedSecret, OracleError> {
+        let key = self.token.get(self.handle, std::any::type_name::<T>())?;
+        Ok(T::element_to_shared_secret(&T::exp(element, &key.private)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::group::{P256, P384, X25519};
+    use crate::hybrid::HybridKem;
+    use crate::hybrid::test::off_curve_ciphertext;
+    use crate::kem::{MlKem1024, MlKem768};
+    use crate::{MlKem1024P384, MlKem768P256, MlKem768X25519};
+
+    fn test_software<PQ: PqKem, T: NominalGroup, H: HybridKem + OracleDecaps>() {
+        let mut rng = rand::rng();
+        let (dk, ek, info) = H::derive_key_pair(&vec![3u8; H::SEED_SIZE]);
+        let pq = SoftwareDecapsOracle::<PQ>::new(info.dk_pq);
+        let t = SoftwareDhOracle::<T>::new(info.dk_t);
+
+        assert_eq!(H::encapsulation_key_with_oracles(&pq, &t).unwrap(), ek);
+
+        let (ss, ct) = H::encaps(&ek, &mut rng);
+        let ss_oracle = H::decaps_with_oracles(&pq, &t, &ct).unwrap();
+        assert_eq!(ss_oracle, ss);
+        assert_eq!(ss_oracle, H::decaps(&dk, &ct));
+
+        assert_eq!(
+            H::decaps_with_oracles(&pq, &t, &ct[1..].to_vec()),
+            Err(OracleError::Ciphertext)
+        );
+    }
+
+    fn test_token<PQ: PqKem, T: NominalGroup, H: HybridKem + OracleDecaps>() {
+        let mut rng = rand::rng();
+        let (dk, ek, info) = H::derive_key_pair(&vec![4u8; H::SEED_SIZE]);
+
+        let mut token = Token::new();
+        let pq_handle = token.import_decaps_key::<PQ>(info.dk_pq);
+        let t_handle = token.import_scalar::<T>(info.dk_t);
+
+        let (ss, ct) = H::encaps(&ek, &mut rng);
+        {
+            let pq = token.decaps_oracle::<PQ>(pq_handle);
+            let t = token.dh_oracle::<T>(t_handle);
+            assert_eq!(H::encapsulation_key_with_oracles(&pq, &t).unwrap(), ek);
+            assert_eq!(H::decaps_with_oracles(&pq, &t, &ct).unwrap(), ss);
+            assert_eq!(H::decaps(&dk, &ct), ss);
+
+            // Handles are bound to the type of key they were imported as
+            let swapped = token.dh_oracle::<T>(pq_handle);
+            assert_eq!(
+                H::decaps_with_oracles(&pq, &swapped, &ct),
+                Err(OracleError::WrongKeyType)
+            );
+        }
+
+        token.destroy(t_handle).unwrap();
+        let pq = token.decaps_oracle::<PQ>(pq_handle);
+        let t = token.dh_oracle::<T>(t_handle);
+        assert_eq!(
+            H::decaps_with_oracles(&pq, &t, &ct),
+            Err(OracleError::UnknownHandle)
+        );
+        assert_eq!(token.destroy(t_handle), Err(OracleError::UnknownHandle));
+    }
+
+    // An invalid traditional ciphertext must be caught before it reaches the DH oracle
+    fn test_invalid_point<PQ: PqKem, T: NominalGroup, H: HybridKem + OracleDecaps>() {
+        let (_, ek, info) = H::derive_key_pair(&vec![5u8; H::SEED_SIZE]);
+        let pq = SoftwareDecapsOracle::<PQ>::new(info.dk_pq);
+        let t = SoftwareDhOracle::<T>::new(info.dk_t);
+
+        let ct = off_curve_ciphertext::<H>(&ek);
+        assert_eq!(
+            H::decaps_with_oracles(&pq, &t, &ct),
+            Err(OracleError::Ciphertext)
+        );
+    }
+
+    #[test]
+    fn mlkem768_p256() {
+        test_invalid_point::<MlKem768, P256, MlKem768P256>();
+        test_software::<MlKem768, P256, MlKem768P256>();
+        test_token::<MlKem768, P256, MlKem768P256>();
+    }
+
+    #[test]
+    fn mlkem768_x25519() {
+        test_software::<MlKem768, X25519, MlKem768X25519>();
+        test_token::<MlKem768, X25519, MlKem768X25519>();
+    }
+
+    #[test]
+    fn mlkem1024_p384() {
+        test_software::<MlKem1024, P384, MlKem1024P384>();
+        test_token::<MlKem1024, P384, MlKem1024P384>();
+        test_invalid_point::<MlKem1024, P384, MlKem1024P384>();
+    }
+}
@@ -0,0 +1,334 @@
+//! Public-key encryption from a hybrid KEM and an AEAD (KEM-DEM)
+//!
+//! A sealed message has the following layout:
+//!
+//! ~~~text
+//! ct_h || nonce || aead_ct
+//! ~~~
+//!
+//! where `ct_h` is the hybrid KEM ciphertext (`K::CIPHERTEXT_SIZE` bytes), `nonce` is the AEAD
+//! nonce (`A::NONCE_SIZE` bytes), and `aead_ct` is the AEAD ciphertext including its tag
+//! (`plaintext.len() + A::TAG_SIZE` bytes). The AEAD key is derived from the hybrid shared secret
+//! as `SHA3-256(ss_h || A::LABEL)`, so the same KEM key pair can be used with either suite
+//! without the two keys being related.
+
+use crate::hybrid::HybridKem;
+use crate::kdf::{Kdf, Sha3_256};
+use crate::kem::{Ciphertext, DecapsulationKey, EncapsulationKey, SharedSecret};
+use rand::{CryptoRng, Rng};
+
+/// An enumeration of the ways sealing a message can fail
+#[derive(Debug, PartialEq, Eq)]
+pub enum SealError {
+    /// The encapsulation key has the wrong length
+    KeyLength,
+    /// The encapsulation key is malformed (e.g., its traditional component is not a point on the
+    /// curve)
+    EncapsulationKey,
+    /// `seal_derand` was given the wrong amount of randomness
+    RandomnessLength,
+}
+
+/// An enumeration of the ways opening a sealed message can fail
+#[derive(Debug, PartialEq, Eq)]
+pub enum OpenError {
+    /// The message is too short to contain a KEM ciphertext, nonce, and tag
+    Length,
+    /// The decapsulation key has the wrong length
+    KeyLength,
+    /// The KEM ciphertext is malformed (e.g., its traditional component is not a point on the
+    /// curve)
+    Ciphertext,
+    /// The AEAD tag did not verify
+    Tag,
+}
+
+pub trait Aead {
+    const KEY_SIZE: usize;
+    const NONCE_SIZE: usize;
+    const TAG_SIZE: usize;
+
+    /// Domain separation label used when deriving the AEAD key
+    const LABEL: &'static [u8];
+
+    fn seal(key: &[u8], nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Vec<u8>;
+    fn open(key: &[u8], nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>>;
+}
+
+macro_rules! define_aead {
+    ($aead:ident, $mod:ident, $label:expr) => {
+        pub struct $aead;
+
+        impl Aead for $aead {
+            const KEY_SIZE: usize = 32;
+            const NONCE_SIZE: usize = 12;
+            const TAG_SIZE: usize = 16;
+
+            const LABEL: &'static [u8] = $label;
+
+            fn seal(key: &[u8], nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+                use $mod::aead::{Aead, KeyInit, Payload};
+
+                assert_eq!(key.len(), Self::KEY_SIZE);
+                assert_eq!(nonce.len(), Self::NONCE_SIZE);
+
+                let cipher = $mod::$aead::new_from_slice(key).expect("Invalid key size");
+                let payload = Payload {
+                    msg: plaintext,
+                    aad,
+                };
+                cipher
+                    .encrypt(nonce.into(), payload)
+                    .expect("Encryption failed")
+            }
+
+            fn open(key: &[u8], nonce: &[u8], aad: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>> {
+                use $mod::aead::{Aead, KeyInit, Payload};
+
+                assert_eq!(key.len(), Self::KEY_SIZE);
+                assert_eq!(nonce.len(), Self::NONCE_SIZE);
+
+                let cipher = $mod::$aead::new_from_slice(key).expect("Invalid key size");
+                let payload = Payload {
+                    msg: ciphertext,
+                    aad,
+                };
+                cipher.decrypt(nonce.into(), payload).ok()
+            }
+        }
+    };
+}
+
+define_aead! { ChaCha20Poly1305, chacha20poly1305, b"ChaCha20-Poly1305" }
+define_aead! { Aes256Gcm, aes_gcm, b"AES-256-GCM" }
+
+fn derive_key<A: Aead>(ss: &SharedSecret) -> Vec<u8> {
+    let key = Sha3_256::compute(&[ss, A::LABEL]);
+    key[..A::KEY_SIZE].to_vec()
+}
+
+fn check_encapsulation_key<K: HybridKem>(ek: &EncapsulationKey) -> Result<(), SealError> {
+    if ek.len() != K::ENCAPSULATION_KEY_SIZE {
+        return Err(SealError::KeyLength);
+    }
+    if !K::validate_encapsulation_key(ek) {
+        return Err(SealError::EncapsulationKey);
+    }
+    Ok(())
+}
+
+fn seal_with<A: Aead>(
+    ct_h: Ciphertext,
+    ss_h: &SharedSecret,
+    nonce: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Vec<u8> {
+    let key = derive_key::<A>(ss_h);
+    let mut ct = ct_h;
+    ct.extend_from_slice(nonce);
+    ct.append(&mut A::seal(&key, nonce, aad, plaintext));
+    ct
+}
+
+/// Encrypt `plaintext` to the holder of `ek`, authenticating `aad`
+pub fn seal<K: HybridKem, A: Aead>(
+    ek: &EncapsulationKey,
+    aad: &[u8],
+    plaintext: &[u8],
+    rng: &mut impl CryptoRng,
+) -> Result<Vec<u8>, SealError> {
+    check_encapsulation_key::<K>(ek)?;
+    let (ss_h, ct_h) = K::encaps(ek, rng);
+
+    let mut nonce = vec![0u8; A::NONCE_SIZE];
+    rng.fill(nonce.as_mut_slice());
+
+    Ok(seal_with::<A>(ct_h, &ss_h, &nonce, aad, plaintext))
+}
+
+/// Deterministic variant of `seal`, with `randomness = randomness_kem || nonce`
+pub fn seal_derand<K: HybridKem, A: Aead>(
+    ek: &EncapsulationKey,
+    aad: &[u8],
+    plaintext: &[u8],
+    randomness: &[u8],
+) -> Result<Vec<u8>, SealError> {
+    check_encapsulation_key::<K>(ek)?;
+    if randomness.len() != K::RANDOMNESS_SIZE + A::NONCE_SIZE {
+        return Err(SealError::RandomnessLength);
+    }
+
+    let (randomness_kem, nonce) = randomness.split_at(K::RANDOMNESS_SIZE);
+    let (ct_h, ss_h) = K::encaps_derand(ek, randomness_kem);
+
+    Ok(seal_with::<A>(ct_h, &ss_h, nonce, aad, plaintext))
+}
+
+/// Decrypt a message produced by `seal`, authenticating `aad`
+pub fn open<K: HybridKem, A: Aead>(
+    dk: &DecapsulationKey,
+    aad: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, OpenError> {
+    if dk.len() != K::DECAPSULATION_KEY_SIZE {
+        return Err(OpenError::KeyLength);
+    }
+    if ciphertext.len() < K::CIPHERTEXT_SIZE + A::NONCE_SIZE + A::TAG_SIZE {
+        return Err(OpenError::Length);
+    }
+
+    let (ct_h, rest) = ciphertext.split_at(K::CIPHERTEXT_SIZE);
+    let (nonce, aead_ct) = rest.split_at(A::NONCE_SIZE);
+    if !K::validate_ciphertext(ct_h) {
+        return Err(OpenError::Ciphertext);
+    }
+
+    let ss_h = K::decaps(dk, &ct_h.to_vec());
+    let key = derive_key::<A>(&ss_h);
+    A::open(&key, nonce, aad, aead_ct).ok_or(OpenError::Tag)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hybrid::test::{off_curve_ciphertext, off_curve_encapsulation_key};
+    use crate::{MlKem768P256, MlKem768X25519, MlKem1024P384};
+    use hex_literal::hex;
+
+    fn test_roundtrip<K: HybridKem, A: Aead>() {
+        let mut rng = rand::rng();
+        let (dk, ek, _) = K::derive_key_pair(&vec![1u8; K::SEED_SIZE]);
+        let aad = b"associated data";
+        let plaintext = b"attack at dawn";
+
+        let ct = seal::<K, A>(&ek, aad, plaintext, &mut rng).unwrap();
+        assert_eq!(
+            ct.len(),
+            K::CIPHERTEXT_SIZE + A::NONCE_SIZE + plaintext.len() + A::TAG_SIZE,
+            "Sealed message size mismatch"
+        );
+        assert_eq!(open::<K, A>(&dk, aad, &ct).unwrap(), plaintext);
+
+        // Empty plaintexts are allowed
+        let ct = seal::<K, A>(&ek, aad, b"", &mut rng).unwrap();
+        assert_eq!(open::<K, A>(&dk, aad, &ct).unwrap(), b"");
+    }
+
+    fn test_tampering<K: HybridKem, A: Aead>() {
+        let (dk, ek, _) = K::derive_key_pair(&vec![2u8; K::SEED_SIZE]);
+        let randomness = vec![3u8; K::RANDOMNESS_SIZE + A::NONCE_SIZE];
+        let aad = b"associated data";
+        let ct = seal_derand::<K, A>(&ek, aad, b"attack at dawn", &randomness).unwrap();
+
+        // Flip a bit in each of the KEM ciphertext, the nonce, and the AEAD ciphertext
+        for i in [0, K::CIPHERTEXT_SIZE, ct.len() - 1] {
+            let mut bad = ct.clone();
+            bad[i] ^= 0x01;
+            assert_eq!(open::<K, A>(&dk, aad, &bad), Err(OpenError::Tag));
+        }
+
+        assert_eq!(open::<K, A>(&dk, b"other", &ct), Err(OpenError::Tag));
+
+        let short = &ct[..K::CIPHERTEXT_SIZE + A::NONCE_SIZE + A::TAG_SIZE - 1];
+        assert_eq!(open::<K, A>(&dk, aad, short), Err(OpenError::Length));
+        assert_eq!(
+            open::<K, A>(&dk[1..].to_vec(), aad, &ct),
+            Err(OpenError::KeyLength)
+        );
+
+        // A different key pair cannot open the message
+        let (other_dk, _, _) = K::derive_key_pair(&vec![4u8; K::SEED_SIZE]);
+        assert_eq!(open::<K, A>(&other_dk, aad, &ct), Err(OpenError::Tag));
+    }
+
+    fn test_seal_lengths<K: HybridKem, A: Aead>() {
+        let (_, ek, _) = K::derive_key_pair(&vec![7u8; K::SEED_SIZE]);
+        let randomness = vec![8u8; K::RANDOMNESS_SIZE + A::NONCE_SIZE];
+
+        let short_ek = ek[1..].to_vec();
+        let result = seal::<K, A>(&short_ek, b"", b"", &mut rand::rng());
+        assert_eq!(result, Err(SealError::KeyLength));
+        let result = seal_derand::<K, A>(&short_ek, b"", b"", &randomness);
+        assert_eq!(result, Err(SealError::KeyLength));
+
+        let result = seal_derand::<K, A>(&ek, b"", b"", &randomness[1..]);
+        assert_eq!(result, Err(SealError::RandomnessLength));
+    }
+
+    fn test_all<K: HybridKem>() {
+        test_roundtrip::<K, ChaCha20Poly1305>();
+        test_roundtrip::<K, Aes256Gcm>();
+        test_tampering::<K, ChaCha20Poly1305>();
+        test_tampering::<K, Aes256Gcm>();
+        test_seal_lengths::<K, ChaCha20Poly1305>();
+    }
+
+    #[test]
+    fn mlkem768_p256() {
+        test_all::<MlKem768P256>();
+    }
+
+    fn test_invalid_point<K: HybridKem, A: Aead>() {
+        let (dk, ek, _) = K::derive_key_pair(&vec![5u8; K::SEED_SIZE]);
+        let randomness = vec![6u8; K::RANDOMNESS_SIZE + A::NONCE_SIZE];
+        let ct = seal_derand::<K, A>(&ek, b"", b"attack at dawn", &randomness).unwrap();
+
+        let ct_h = off_curve_ciphertext::<K>(&ek);
+        let ct = [ct_h.as_slice(), &ct[K::CIPHERTEXT_SIZE..]].concat();
+        assert_eq!(open::<K, A>(&dk, b"", &ct), Err(OpenError::Ciphertext));
+
+        let ek = off_curve_encapsulation_key::<K>(&ek);
+        let result = seal::<K, A>(&ek, b"", b"attack at dawn", &mut rand::rng());
+        assert_eq!(result, Err(SealError::EncapsulationKey));
+        let result = seal_derand::<K, A>(&ek, b"", b"attack at dawn", &randomness);
+        assert_eq!(result, Err(SealError::EncapsulationKey));
+    }
+
+    #[test]
+    fn invalid_point() {
+        test_invalid_point::<MlKem768P256, ChaCha20Poly1305>();
+        test_invalid_point::<MlKem1024P384, Aes256Gcm>();
+    }
+
+    #[test]
+    fn mlkem768_x25519() {
+        test_all::<MlKem768X25519>();
+    }
+
+    #[test]
+    fn mlkem1024_p384() {
+        test_all::<MlKem1024P384>();
+    }
+
+    // Test vectors use the first entry of test-vectors.json for the KEM, so only the part of the
+    // message after `ct_h` is listed here.
+    fn test_vector<K: HybridKem, A: Aead>(expected: &[u8]) {
+        let seed = vec![0u8; K::SEED_SIZE];
+        let randomness = [vec![100u8; K::RANDOMNESS_SIZE], vec![200u8; A::NONCE_SIZE]].concat();
+        let (dk, ek, _) = K::derive_key_pair(&seed);
+        let (ct_h, _) = K::encaps_derand(&ek, &randomness[..K::RANDOMNESS_SIZE]);
+
+        let ct = seal_derand::<K, A>(&ek, b"aad", b"plaintext", &randomness).unwrap();
+        assert_eq!(ct[..K::CIPHERTEXT_SIZE], ct_h);
+        assert_eq!(
+            hex::encode(&ct[K::CIPHERTEXT_SIZE..]),
+            hex::encode(expected)
+        );
+        assert_eq!(open::<K, A>(&dk, b"aad", &ct).unwrap(), b"plaintext");
+    }
+
+    #[test]
+    fn mlkem768_x25519_chacha20poly1305_vector() {
+        test_vector::<MlKem768X25519, ChaCha20Poly1305>(&hex!(
+            "c8c8c8c8c8c8c8c8c8c8c8c8" "07cdd2da9af8411f01" "8ea8aa794c8cc8a70fec3b2a582935f2"
+        ));
+    }
+
+    #[test]
+    fn mlkem768_x25519_aes256gcm_vector() {
+        test_vector::<MlKem768X25519, Aes256Gcm>(&hex!(
+            "c8c8c8c8c8c8c8c8c8c8c8c8" "6fa14e150148d5875d" "cf0c7afe0f085448d547f808ba126646"
+        ));
+    }
+}
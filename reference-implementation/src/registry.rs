@@ -0,0 +1,270 @@
+//! The "Hybrid KEM Labels" registry
+//!
+//! Each hybrid KEM instance is identified by a six-byte label that is fed to its combiner.  The
+//! entries below are built from the instance types themselves: the label and sizes come from the
+//! instance, and the framework and component names from `Registered` and `ComponentName`.  The
+//! IANA table in the draft is generated from them rather than maintained by hand.
+
+use crate::algorithm::Algorithm;
+use crate::group::{NominalGroup, P256, P384, X25519};
+use crate::hybrid::{GC, GU, HybridKemConstants};
+use crate::kdf::{Kdf, Sha3_256};
+use crate::kem::{MlKem768, MlKem1024, PqKem, SeedSize, SharedSecretSize};
+use crate::prg::{Prg, Shake256};
+use crate::{MlKem768P256, MlKem768X25519, MlKem1024P384};
+
+/// The name of a component as it appears in the registry
+pub trait ComponentName {
+    const NAME: &'static str;
+}
+
+macro_rules! impl_component_name {
+    ($($component:ident => $name:literal),* $(,)?) => {
+        $(
+            impl ComponentName for $component {
+                const NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+impl_component_name! {
+    MlKem768 => "ML-KEM-768",
+    MlKem1024 => "ML-KEM-1024",
+    P256 => "P-256",
+    P384 => "P-384",
+    X25519 => "Curve25519",
+    Sha3_256 => "SHA3-256",
+    Shake256 => "SHAKE-256",
+}
+
+/// A hybrid KEM type that can be listed in the registry
+pub trait Registered: SeedSize + SharedSecretSize {
+    /// The framework, named as in the draft ("CG" is the `GC` framework in this crate)
+    const FRAMEWORK: &'static str;
+    const LABEL: &'static [u8];
+    const PQ: &'static str;
+    const T: &'static str;
+    const KDF: &'static str;
+    const PRG: &'static str;
+}
+
+macro_rules! impl_registered {
+    ($framework:ident, $name:literal) => {
+        impl<PQ, T, P, K, C> Registered for $framework<PQ, T, P, K, C>
+        where
+            PQ: PqKem + ComponentName,
+            T: NominalGroup + ComponentName,
+            P: Prg + ComponentName,
+            K: Kdf + ComponentName,
+            C: HybridKemConstants,
+        {
+            const FRAMEWORK: &'static str = $name;
+            const LABEL: &'static [u8] = C::LABEL;
+            const PQ: &'static str = PQ::NAME;
+            const T: &'static str = T::NAME;
+            const KDF: &'static str = K::NAME;
+            const PRG: &'static str = P::NAME;
+        }
+    };
+}
+
+impl_registered!(GU, "UG");
+impl_registered!(GC, "CG");
+
+/// A row of the registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub algorithm: Algorithm,
+    pub label: &'static [u8],
+    /// The framework, named as in the draft ("CG" is the `GC` framework in this crate)
+    pub framework: &'static str,
+    pub pq: &'static str,
+    pub t: &'static str,
+    pub kdf: &'static str,
+    pub prg: &'static str,
+    pub seed_size: usize,
+    pub shared_secret_size: usize,
+}
+
+impl Entry {
+    const fn new<H: Registered>(algorithm: Algorithm) -> Self {
+        Entry {
+            algorithm,
+            label: H::LABEL,
+            framework: H::FRAMEWORK,
+            pq: H::PQ,
+            t: H::T,
+            kdf: H::KDF,
+            prg: H::PRG,
+            seed_size: H::SEED_SIZE,
+            shared_secret_size: H::SHARED_SECRET_SIZE,
+        }
+    }
+}
+
+/// Build one entry per instance type, each `Algorithm` variant being named after its type
+macro_rules! entries {
+    ($($instance:ident),* $(,)?) => {
+        [$(Entry::new::<$instance>(Algorithm::$instance)),*]
+    };
+}
+
+pub const ENTRIES: [Entry; 3] = entries![MlKem768P256, MlKem768X25519, MlKem1024P384];
+
+impl Algorithm {
+    /// The registry entry for the instance
+    pub fn registry_entry(self) -> &'static Entry {
+        ENTRIES
+            .iter()
+            .find(|e| e.algorithm == self)
+            .expect("Algorithm missing from registry")
+    }
+}
+
+/// Look up the instance that uses `label`
+pub fn by_label(label: &[u8]) -> Option<&'static Entry> {
+    ENTRIES.iter().find(|e| e.label == label)
+}
+
+/// Quote a label for a markdown table cell, escaping the characters that kramdown interprets
+fn quote_label(label: &[u8]) -> String {
+    let label = String::from_utf8(label.to_vec()).expect("Labels are ASCII");
+    format!("\"{}\"", label.replace('\\', "\\\\").replace('|', "\\|"))
+}
+
+/// Render the registry as the markdown table in the IANA Considerations of the draft
+pub fn iana_table() -> String {
+    let header = [
+        "Label",
+        "Fw",
+        "PQ Component",
+        "T Component",
+        "KDF",
+        "PRG",
+        "Nseed",
+        "Nss",
+        "Reference",
+    ];
+    let rows: Vec<[String; 9]> = ENTRIES
+        .iter()
+        .map(|e| {
+            [
+                quote_label(e.label),
+                e.framework.to_string(),
+                e.pq.to_string(),
+                e.t.to_string(),
+                e.kdf.to_string(),
+                e.prg.to_string(),
+                e.seed_size.to_string(),
+                e.shared_secret_size.to_string(),
+                "[RFCXXXX]".to_string(),
+            ]
+        })
+        .collect();
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|r| r[i].len())
+                .chain([header[i].len()])
+                .max()
+                .unwrap()
+        })
+        .collect();
+
+    let line = |cells: &[&str]| -> String {
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(c, w)| format!(" {:w$} ", c, w = w))
+            .collect();
+        format!("|{}|\n", cells.join("|"))
+    };
+
+    let mut table = line(&header);
+    let rule: Vec<String> = widths.iter().map(|w| "=".repeat(w + 2)).collect();
+    table += &format!("|{}|\n", rule.join("|"));
+    for row in &rows {
+        let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+        table += &line(&cells);
+    }
+    table += "{: #iana-table title=\"Hybrid KEM Labels\" }\n";
+    table
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn unique_labels() {
+        let mut labels = HashSet::new();
+        for entry in &ENTRIES {
+            assert_eq!(entry.label.len(), 6, "{}", entry.algorithm);
+            assert!(entry.label.is_ascii(), "{}", entry.algorithm);
+            assert!(
+                labels.insert(entry.label),
+                "Duplicate label {:?} for {}",
+                entry.label,
+                entry.algorithm
+            );
+        }
+    }
+
+    #[test]
+    fn complete() {
+        for algorithm in Algorithm::ALL {
+            let entry = algorithm.registry_entry();
+            assert_eq!(by_label(entry.label), Some(entry));
+            assert_eq!(entry.seed_size, algorithm.kem().seed_size());
+            assert_eq!(
+                entry.shared_secret_size,
+                algorithm.kem().shared_secret_size()
+            );
+        }
+        assert_eq!(ENTRIES.len(), Algorithm::ALL.len());
+        assert_eq!(by_label(b"XXXXXX"), None);
+    }
+
+    // The names come from the types, so a GU instance on the same components differs only in
+    // its framework
+    #[test]
+    fn registered() {
+        use crate::MlKem768X25519Constants;
+
+        type Gu = GU<MlKem768, X25519, Shake256, Sha3_256, MlKem768X25519Constants>;
+        let gu = Entry::new::<Gu>(Algorithm::MlKem768X25519);
+        let gc = Algorithm::MlKem768X25519.registry_entry();
+        assert_eq!(gu.framework, "UG");
+        assert_eq!(
+            Entry {
+                framework: "CG",
+                ..gu
+            },
+            *gc
+        );
+    }
+
+    #[test]
+    fn table() {
+        let expected = concat!(
+            "| Label      | Fw | PQ Component | T Component | KDF      | PRG       | Nseed | Nss | Reference |\n",
+            "|============|====|==============|=============|==========|===========|=======|=====|===========|\n",
+            "| \"\\|-()-\\|\" | CG | ML-KEM-768   | P-256       | SHA3-256 | SHAKE-256 | 32    | 32  | [RFCXXXX] |\n",
+            "| \"\\\\.//^\\\\\" | CG | ML-KEM-768   | Curve25519  | SHA3-256 | SHAKE-256 | 32    | 32  | [RFCXXXX] |\n",
+            "| \" \\| /-\\\\\" | CG | ML-KEM-1024  | P-384       | SHA3-256 | SHAKE-256 | 32    | 32  | [RFCXXXX] |\n",
+            "{: #iana-table title=\"Hybrid KEM Labels\" }\n",
+        );
+        assert_eq!(iana_table(), expected);
+    }
+
+    // The table included by the draft matches the registry
+    #[test]
+    fn draft_table() {
+        let draft = include_str!("../../draft-irtf-cfrg-concrete-hybrid-kems.md");
+        assert!(draft.contains("{::include iana-table.md}"));
+        assert_eq!(include_str!("../../iana-table.md"), iana_table());
+    }
+}
@@ -0,0 +1,628 @@
+//! Streaming file encryption to multiple hybrid KEM recipients
+//!
+//! The format follows the design of age: a random file key is sealed to each recipient with the
+//! `pke` module, and the payload is encrypted under a key derived from the file key using the
+//! STREAM construction with ChaCha20-Poly1305.
+//!
+//! ~~~text
+//! header  = MAGIC || count (u16, big-endian) || stanza * count || header_tag
+//! stanza  = algorithm id (u8) || pke::seal(ek, MAGIC, file_key)
+//! payload = payload_nonce || chunk * n
+//! ~~~
+//!
+//! * `header_tag` is a ChaCha20-Poly1305 tag over an empty plaintext, with the preceding header
+//!   bytes as associated data and `SHA3-256(file_key || "header")` as the key.
+//! * The payload key is `SHA3-256(file_key || "payload" || payload_nonce)`.
+//! * Each chunk encrypts `CHUNK_SIZE` bytes of plaintext (the final chunk may be shorter) under
+//!   the nonce `counter (11 bytes, big-endian) || last`, where `last` is 1 for the final chunk and
+//!   0 otherwise. Only an empty input produces an empty final chunk.
+//!
+//! Decryption writes out each chunk as soon as it is authenticated, so memory use is bounded by
+//! the chunk size. Truncation is reported when the end of the input is reached, after the
+//! preceding chunks have been written.
+
+use crate::hybrid::{HybridKem, InputValidation};
+use crate::kdf::{Kdf, Sha3_256};
+use crate::kem::{DecapsulationKey, EncapsulationKey, Kem};
+use crate::pke::{self, Aead, ChaCha20Poly1305};
+use crate::{MlKem768P256, MlKem768X25519, MlKem1024P384};
+use rand::{CryptoRng, Rng};
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+
+const MAGIC: &[u8] = b"HKEMSTv1";
+const FILE_KEY_SIZE: usize = 16;
+const PAYLOAD_NONCE_SIZE: usize = 16;
+const TAG_SIZE: usize = 16;
+
+/// The amount of plaintext in each payload chunk
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+const ENCRYPTED_CHUNK_SIZE: usize = CHUNK_SIZE + TAG_SIZE;
+
+/// An enumeration of the ways encryption and decryption can fail
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// The input is not a well-formed encrypted stream
+    Format,
+    /// No stanza in the header could be opened with the identity
+    NoMatchingRecipient,
+    /// The header failed authentication
+    Header,
+    /// A payload chunk failed authentication, or the payload was truncated or reordered
+    Payload,
+    /// A recipient or identity string could not be parsed, or a recipient's key is invalid
+    Parse,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "I/O error: {}", err),
+            Error::Format => write!(f, "malformed input"),
+            Error::NoMatchingRecipient => write!(f, "no matching recipient"),
+            Error::Header => write!(f, "header authentication failed"),
+            Error::Payload => write!(f, "payload authentication failed"),
+            Error::Parse => write!(f, "invalid recipient or identity"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+/// The hybrid KEMs that can be used as recipients
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    MlKem768X25519,
+    MlKem1024P384,
+    MlKem768P256,
+}
+
+impl Algorithm {
+    fn id(self) -> u8 {
+        match self {
+            Algorithm::MlKem768X25519 => 0x01,
+            Algorithm::MlKem1024P384 => 0x02,
+            Algorithm::MlKem768P256 => 0x03,
+        }
+    }
+
+    fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0x01 => Some(Algorithm::MlKem768X25519),
+            0x02 => Some(Algorithm::MlKem1024P384),
+            0x03 => Some(Algorithm::MlKem768P256),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Algorithm::MlKem768X25519 => "MLKEM768-X25519",
+            Algorithm::MlKem1024P384 => "MLKEM1024-P384",
+            Algorithm::MlKem768P256 => "MLKEM768-P256",
+        }
+    }
+
+    fn stanza_size(self) -> usize {
+        match self {
+            Algorithm::MlKem768X25519 => stanza_size::<MlKem768X25519>(),
+            Algorithm::MlKem1024P384 => stanza_size::<MlKem1024P384>(),
+            Algorithm::MlKem768P256 => stanza_size::<MlKem768P256>(),
+        }
+    }
+
+    fn decapsulation_key_size(self) -> usize {
+        match self {
+            Algorithm::MlKem768X25519 => MlKem768X25519::DECAPSULATION_KEY_SIZE,
+            Algorithm::MlKem1024P384 => MlKem1024P384::DECAPSULATION_KEY_SIZE,
+            Algorithm::MlKem768P256 => MlKem768P256::DECAPSULATION_KEY_SIZE,
+        }
+    }
+
+    /// Whether `ek` has the right length and its traditional component is a point on the curve,
+    /// which `pke::seal` requires
+    fn validate_encapsulation_key(self, ek: &[u8]) -> bool {
+        match self {
+            Algorithm::MlKem768X25519 => MlKem768X25519::validate_encapsulation_key(ek),
+            Algorithm::MlKem1024P384 => MlKem1024P384::validate_encapsulation_key(ek),
+            Algorithm::MlKem768P256 => MlKem768P256::validate_encapsulation_key(ek),
+        }
+    }
+}
+
+/// A public key that a stream can be encrypted to, written as `NAME:hex(ek)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipient {
+    pub algorithm: Algorithm,
+    pub ek: EncapsulationKey,
+}
+
+/// A private key that can decrypt a stream, written as `NAME:hex(dk)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    pub algorithm: Algorithm,
+    pub dk: DecapsulationKey,
+}
+
+impl Identity {
+    /// Generate a fresh identity for the given algorithm
+    pub fn generate(algorithm: Algorithm, rng: &mut impl CryptoRng) -> Self {
+        let mut dk = vec![0u8; algorithm.decapsulation_key_size()];
+        rng.fill(dk.as_mut_slice());
+        Identity { algorithm, dk }
+    }
+
+    /// The recipient corresponding to this identity
+    pub fn to_recipient(&self) -> Recipient {
+        let ek = match self.algorithm {
+            Algorithm::MlKem768X25519 => MlKem768X25519::derive_key_pair(&self.dk).1,
+            Algorithm::MlKem1024P384 => MlKem1024P384::derive_key_pair(&self.dk).1,
+            Algorithm::MlKem768P256 => MlKem768P256::derive_key_pair(&self.dk).1,
+        };
+        Recipient {
+            algorithm: self.algorithm,
+            ek,
+        }
+    }
+}
+
+fn parse_key(s: &str) -> Result<(Algorithm, Vec<u8>), Error> {
+    let (name, key) = s.trim().split_once(':').ok_or(Error::Parse)?;
+    let algorithm = name.parse()?;
+    let key = hex::decode(key).map_err(|_| Error::Parse)?;
+    Ok((algorithm, key))
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "MLKEM768-X25519" => Ok(Algorithm::MlKem768X25519),
+            "MLKEM1024-P384" => Ok(Algorithm::MlKem1024P384),
+            "MLKEM768-P256" => Ok(Algorithm::MlKem768P256),
+            _ => Err(Error::Parse),
+        }
+    }
+}
+
+impl FromStr for Recipient {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let (algorithm, ek) = parse_key(s)?;
+        if !algorithm.validate_encapsulation_key(&ek) {
+            return Err(Error::Parse);
+        }
+        Ok(Recipient { algorithm, ek })
+    }
+}
+
+impl FromStr for Identity {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let (algorithm, dk) = parse_key(s)?;
+        if dk.len() != algorithm.decapsulation_key_size() {
+            return Err(Error::Parse);
+        }
+        Ok(Identity { algorithm, dk })
+    }
+}
+
+impl fmt::Display for Recipient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.algorithm.name(), hex::encode(&self.ek))
+    }
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.algorithm.name(), hex::encode(&self.dk))
+    }
+}
+
+fn stanza_size<K: HybridKem>() -> usize {
+    K::CIPHERTEXT_SIZE + ChaCha20Poly1305::NONCE_SIZE + FILE_KEY_SIZE + ChaCha20Poly1305::TAG_SIZE
+}
+
+fn wrap_file_key(
+    recipient: &Recipient,
+    file_key: &[u8],
+    rng: &mut impl CryptoRng,
+) -> Result<Vec<u8>, Error> {
+    type A = ChaCha20Poly1305;
+    let stanza = match recipient.algorithm {
+        Algorithm::MlKem768X25519 => {
+            pke::seal::<MlKem768X25519, A>(&recipient.ek, MAGIC, file_key, rng)
+        }
+        Algorithm::MlKem1024P384 => {
+            pke::seal::<MlKem1024P384, A>(&recipient.ek, MAGIC, file_key, rng)
+        }
+        Algorithm::MlKem768P256 => {
+            pke::seal::<MlKem768P256, A>(&recipient.ek, MAGIC, file_key, rng)
+        }
+    };
+    stanza.map_err(|_| Error::Parse)
+}
+
+fn unwrap_file_key(identity: &Identity, stanza: &[u8]) -> Option<Vec<u8>> {
+    type A = ChaCha20Poly1305;
+    let file_key = match identity.algorithm {
+        Algorithm::MlKem768X25519 => pke::open::<MlKem768X25519, A>(&identity.dk, MAGIC, stanza),
+        Algorithm::MlKem1024P384 => pke::open::<MlKem1024P384, A>(&identity.dk, MAGIC, stanza),
+        Algorithm::MlKem768P256 => pke::open::<MlKem768P256, A>(&identity.dk, MAGIC, stanza),
+    };
+    file_key.ok().filter(|k| k.len() == FILE_KEY_SIZE)
+}
+
+fn header_key(file_key: &[u8]) -> Vec<u8> {
+    Sha3_256::compute(&[file_key, b"header"])
+}
+
+fn payload_key(file_key: &[u8], payload_nonce: &[u8]) -> Vec<u8> {
+    Sha3_256::compute(&[file_key, b"payload", payload_nonce])
+}
+
+fn chunk_nonce(counter: u128, last: bool) -> Vec<u8> {
+    assert!(counter < 1 << 88, "chunk counter overflow");
+    let mut nonce = counter.to_be_bytes()[5..].to_vec();
+    nonce.push(last as u8);
+    nonce
+}
+
+/// Fill `buf` from `input`, stopping early only at the end of the input
+fn read_full(input: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match input.read(&mut buf[n..]) {
+            Ok(0) => break,
+            Ok(k) => n += k,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(n)
+}
+
+/// Encrypt `input` to every recipient in `recipients`, writing the result to `output`
+pub fn encrypt(
+    recipients: &[Recipient],
+    mut input: impl Read,
+    mut output: impl Write,
+    rng: &mut impl CryptoRng,
+) -> Result<(), Error> {
+    assert!(!recipients.is_empty(), "encrypt: no recipients");
+    assert!(
+        recipients.len() <= u16::MAX as usize,
+        "encrypt: too many recipients"
+    );
+
+    let mut file_key = [0u8; FILE_KEY_SIZE];
+    rng.fill(&mut file_key);
+
+    let mut header = MAGIC.to_vec();
+    header.extend_from_slice(&(recipients.len() as u16).to_be_bytes());
+    for recipient in recipients {
+        header.push(recipient.algorithm.id());
+        header.append(&mut wrap_file_key(recipient, &file_key, rng)?);
+    }
+    let nonce = [0u8; 12];
+    let mut tag = ChaCha20Poly1305::seal(&header_key(&file_key), &nonce, &header, b"");
+    header.append(&mut tag);
+    output.write_all(&header)?;
+
+    let mut payload_nonce = [0u8; PAYLOAD_NONCE_SIZE];
+    rng.fill(&mut payload_nonce);
+    output.write_all(&payload_nonce)?;
+    let key = payload_key(&file_key, &payload_nonce);
+
+    // Read one byte past the chunk so that we know whether this is the last one
+    let mut buf = vec![0u8; CHUNK_SIZE + 1];
+    let mut n = read_full(&mut input, &mut buf)?;
+    let mut counter = 0;
+    loop {
+        let last = n <= CHUNK_SIZE;
+        let len = n.min(CHUNK_SIZE);
+        let chunk = ChaCha20Poly1305::seal(&key, &chunk_nonce(counter, last), b"", &buf[..len]);
+        output.write_all(&chunk)?;
+
+        if last {
+            break;
+        }
+
+        buf[0] = buf[CHUNK_SIZE];
+        n = 1 + read_full(&mut input, &mut buf[1..])?;
+        counter += 1;
+    }
+
+    output.flush()?;
+    Ok(())
+}
+
+fn read_header(identity: &Identity, input: &mut impl Read) -> Result<Vec<u8>, Error> {
+    let mut header = vec![0u8; MAGIC.len() + 2];
+    if read_full(input, &mut header)? != header.len() || &header[..MAGIC.len()] != MAGIC {
+        return Err(Error::Format);
+    }
+    let count = u16::from_be_bytes([header[MAGIC.len()], header[MAGIC.len() + 1]]);
+    if count == 0 {
+        return Err(Error::Format);
+    }
+
+    let mut file_key = None;
+    for _ in 0..count {
+        let mut id = [0u8; 1];
+        if read_full(input, &mut id)? != 1 {
+            return Err(Error::Format);
+        }
+        let algorithm = Algorithm::from_id(id[0]).ok_or(Error::Format)?;
+
+        let mut stanza = vec![0u8; algorithm.stanza_size()];
+        if read_full(input, &mut stanza)? != stanza.len() {
+            return Err(Error::Format);
+        }
+
+        if file_key.is_none() && algorithm == identity.algorithm {
+            file_key = unwrap_file_key(identity, &stanza);
+        }
+
+        header.push(id[0]);
+        header.append(&mut stanza);
+    }
+
+    let mut tag = [0u8; TAG_SIZE];
+    if read_full(input, &mut tag)? != TAG_SIZE {
+        return Err(Error::Format);
+    }
+
+    let file_key = file_key.ok_or(Error::NoMatchingRecipient)?;
+    let nonce = [0u8; 12];
+    ChaCha20Poly1305::open(&header_key(&file_key), &nonce, &header, &tag).ok_or(Error::Header)?;
+
+    Ok(file_key)
+}
+
+/// Decrypt `input` with `identity`, writing the plaintext to `output`
+pub fn decrypt(
+    identity: &Identity,
+    mut input: impl Read,
+    mut output: impl Write,
+) -> Result<(), Error> {
+    let file_key = read_header(identity, &mut input)?;
+
+    let mut payload_nonce = [0u8; PAYLOAD_NONCE_SIZE];
+    if read_full(&mut input, &mut payload_nonce)? != PAYLOAD_NONCE_SIZE {
+        return Err(Error::Format);
+    }
+    let key = payload_key(&file_key, &payload_nonce);
+
+    let mut buf = vec![0u8; ENCRYPTED_CHUNK_SIZE + 1];
+    let mut n = read_full(&mut input, &mut buf)?;
+    let mut counter = 0;
+    loop {
+        let last = n <= ENCRYPTED_CHUNK_SIZE;
+        let len = n.min(ENCRYPTED_CHUNK_SIZE);
+
+        // An empty final chunk is only allowed for an empty input
+        if len < TAG_SIZE || (last && len == TAG_SIZE && counter != 0) {
+            return Err(Error::Payload);
+        }
+
+        let chunk = ChaCha20Poly1305::open(&key, &chunk_nonce(counter, last), b"", &buf[..len])
+            .ok_or(Error::Payload)?;
+        output.write_all(&chunk)?;
+
+        if last {
+            break;
+        }
+
+        buf[0] = buf[ENCRYPTED_CHUNK_SIZE];
+        n = 1 + read_full(&mut input, &mut buf[1..])?;
+        counter += 1;
+    }
+
+    output.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn identities() -> (Identity, Identity) {
+        let mut rng = rand::rng();
+        (
+            Identity::generate(Algorithm::MlKem768X25519, &mut rng),
+            Identity::generate(Algorithm::MlKem1024P384, &mut rng),
+        )
+    }
+
+    fn plaintext(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i as u8).wrapping_mul(31)).collect()
+    }
+
+    fn encrypt_to(recipients: &[Recipient], plaintext: &[u8]) -> Vec<u8> {
+        let mut ct = Vec::new();
+        encrypt(recipients, plaintext, &mut ct, &mut rand::rng()).unwrap();
+        ct
+    }
+
+    fn decrypt_with(identity: &Identity, ct: &[u8]) -> Result<Vec<u8>, Error> {
+        let mut pt = Vec::new();
+        decrypt(identity, ct, &mut pt).map(|_| pt)
+    }
+
+    // Offset of the first payload chunk in a stream encrypted to the given recipients
+    fn payload_offset(recipients: &[Recipient]) -> usize {
+        let stanzas: usize = recipients
+            .iter()
+            .map(|r| 1 + r.algorithm.stanza_size())
+            .sum();
+        MAGIC.len() + 2 + stanzas + TAG_SIZE + PAYLOAD_NONCE_SIZE
+    }
+
+    #[test]
+    fn roundtrip() {
+        let (id1, id2) = identities();
+        let id3 = Identity::generate(Algorithm::MlKem768P256, &mut rand::rng());
+        let recipients = [id1.to_recipient(), id2.to_recipient(), id3.to_recipient()];
+
+        for len in [
+            0,
+            1,
+            CHUNK_SIZE - 1,
+            CHUNK_SIZE,
+            CHUNK_SIZE + 1,
+            3 * CHUNK_SIZE + 17,
+        ] {
+            let pt = plaintext(len);
+            let ct = encrypt_to(&recipients, &pt);
+            assert_eq!(decrypt_with(&id1, &ct).unwrap(), pt);
+            assert_eq!(decrypt_with(&id2, &ct).unwrap(), pt);
+            assert_eq!(decrypt_with(&id3, &ct).unwrap(), pt);
+        }
+    }
+
+    #[test]
+    fn wrong_identity() {
+        let (id1, id2) = identities();
+        let ct = encrypt_to(&[id1.to_recipient()], b"hello");
+
+        assert!(matches!(
+            decrypt_with(&id2, &ct),
+            Err(Error::NoMatchingRecipient)
+        ));
+
+        let other = Identity::generate(Algorithm::MlKem768X25519, &mut rand::rng());
+        assert!(matches!(
+            decrypt_with(&other, &ct),
+            Err(Error::NoMatchingRecipient)
+        ));
+    }
+
+    #[test]
+    fn header_tampering() {
+        let (id1, id2) = identities();
+        let recipients = [id1.to_recipient(), id2.to_recipient()];
+        let mut ct = encrypt_to(&recipients, b"hello");
+
+        // Tamper with the second stanza, which id1 does not need to open
+        let offset = MAGIC.len() + 2 + 1 + Algorithm::MlKem768X25519.stanza_size() + 10;
+        ct[offset] ^= 0x01;
+        assert!(matches!(decrypt_with(&id1, &ct), Err(Error::Header)));
+
+        assert!(matches!(decrypt_with(&id1, &ct[..20]), Err(Error::Format)));
+        assert!(matches!(decrypt_with(&id1, b"garbage"), Err(Error::Format)));
+    }
+
+    #[test]
+    fn truncation() {
+        let (id1, _) = identities();
+        let recipients = [id1.to_recipient()];
+        let pt = plaintext(3 * CHUNK_SIZE);
+        let ct = encrypt_to(&recipients, &pt);
+        let offset = payload_offset(&recipients);
+        assert_eq!(ct.len(), offset + 3 * ENCRYPTED_CHUNK_SIZE);
+
+        // Dropping whole chunks leaves a non-final chunk at the end
+        for chunks in 0..3 {
+            let truncated = &ct[..offset + chunks * ENCRYPTED_CHUNK_SIZE];
+            assert!(matches!(decrypt_with(&id1, truncated), Err(Error::Payload)));
+        }
+
+        // Cutting into a chunk breaks its tag
+        let truncated = &ct[..ct.len() - 1];
+        assert!(matches!(decrypt_with(&id1, truncated), Err(Error::Payload)));
+
+        // Trailing data after the final chunk is rejected
+        let mut extended = ct.clone();
+        extended.push(0);
+        assert!(matches!(decrypt_with(&id1, &extended), Err(Error::Payload)));
+    }
+
+    #[test]
+    fn reordering() {
+        let (id1, _) = identities();
+        let recipients = [id1.to_recipient()];
+        let pt = plaintext(3 * CHUNK_SIZE + 100);
+        let ct = encrypt_to(&recipients, &pt);
+        let offset = payload_offset(&recipients);
+
+        let chunk = |i: usize| {
+            let start = offset + i * ENCRYPTED_CHUNK_SIZE;
+            &ct[start..(start + ENCRYPTED_CHUNK_SIZE).min(ct.len())]
+        };
+
+        // Swap the first two chunks
+        let swapped = [&ct[..offset], chunk(1), chunk(0), chunk(2), chunk(3)].concat();
+        assert_eq!(swapped.len(), ct.len());
+        assert!(matches!(decrypt_with(&id1, &swapped), Err(Error::Payload)));
+
+        // Drop a chunk from the middle
+        let dropped = [&ct[..offset], chunk(0), chunk(2), chunk(3)].concat();
+        assert!(matches!(decrypt_with(&id1, &dropped), Err(Error::Payload)));
+
+        // Splice a chunk from another stream into the same position
+        let other = encrypt_to(&recipients, &pt);
+        let other_offset = payload_offset(&recipients);
+        let spliced = [
+            &ct[..offset + ENCRYPTED_CHUNK_SIZE],
+            &other[other_offset + ENCRYPTED_CHUNK_SIZE..other_offset + 2 * ENCRYPTED_CHUNK_SIZE],
+            chunk(2),
+            chunk(3),
+        ]
+        .concat();
+        assert!(matches!(decrypt_with(&id1, &spliced), Err(Error::Payload)));
+    }
+
+    // The right length for MLKEM768-P256, but the traditional component is not a point
+    fn off_curve_recipient() -> String {
+        let ek = vec![0u8; MlKem768P256::ENCAPSULATION_KEY_SIZE];
+        format!("MLKEM768-P256:{}", hex::encode(ek))
+    }
+
+    #[test]
+    fn recipient_strings() {
+        let (id1, id2) = identities();
+        let id3 = Identity::generate(Algorithm::MlKem768P256, &mut rand::rng());
+        for id in [id1, id2, id3] {
+            let recipient = id.to_recipient();
+            assert_eq!(
+                recipient.to_string().parse::<Recipient>().unwrap(),
+                recipient
+            );
+            assert_eq!(id.to_string().parse::<Identity>().unwrap(), id);
+        }
+
+        assert!("MLKEM768-X25519:00".parse::<Recipient>().is_err());
+        assert!(matches!(
+            off_curve_recipient().parse::<Recipient>(),
+            Err(Error::Parse)
+        ));
+        assert!("MLKEM768-P256:00".parse::<Identity>().is_err());
+        assert!("no-separator".parse::<Identity>().is_err());
+    }
+
+    #[test]
+    fn invalid_recipient_key() {
+        // A recipient built directly rather than parsed is checked when the stream is encrypted
+        let identity = Identity::generate(Algorithm::MlKem768P256, &mut rand::rng());
+        let mut recipient = identity.to_recipient();
+        *recipient.ek.last_mut().unwrap() ^= 0x01;
+
+        let mut ct = Vec::new();
+        let result = encrypt(&[recipient], &b"hello"[..], &mut ct, &mut rand::rng());
+        assert!(matches!(result, Err(Error::Parse)));
+        assert!(ct.is_empty());
+    }
+}
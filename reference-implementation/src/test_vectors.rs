@@ -1,19 +1,121 @@
 //! Test vector data structures for serialization
 
+use crate::algorithm::Algorithm;
 use crate::hybrid::HybridKem;
+use crate::{MlKem1024P384, MlKem768P256, MlKem768X25519};
 use serde::{Deserialize, Serialize};
+use sha3::digest::{ExtendableOutput, XofReader};
+use std::fmt;
 
-/// An enumeration of the ways test vector validation can fail
+/// An enumeration of the ways test vector validation can fail.  Each mismatch variant holds the
+/// value computed by this implementation followed by the value in the vector.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum VerifyError {
     EncapsulationKey(Vec<u8>, Vec<u8>),
     DecapsulationKey(Vec<u8>, Vec<u8>),
     Ciphertext(Vec<u8>, Vec<u8>),
     SharedSecretEncaps(Vec<u8>, Vec<u8>),
     SharedSecretDecaps(Vec<u8>, Vec<u8>),
+    /// An input in the vector has the wrong length for the instance, so nothing was computed
+    InputLength {
+        field: &'static str,
+        operation: &'static str,
+        expected: usize,
+        actual: usize,
+    },
 }
 
+impl VerifyError {
+    /// The name of the mismatched field in the vector
+    pub fn field(&self) -> &'static str {
+        match self {
+            VerifyError::EncapsulationKey(..) => "encapsulation_key",
+            VerifyError::DecapsulationKey(..) => "decapsulation_key",
+            VerifyError::Ciphertext(..) => "ciphertext",
+            VerifyError::SharedSecretEncaps(..) | VerifyError::SharedSecretDecaps(..) => {
+                "shared_secret"
+            }
+            VerifyError::InputLength { field, .. } => field,
+        }
+    }
+
+    /// The operation that produced the mismatched value
+    pub fn operation(&self) -> &'static str {
+        match self {
+            VerifyError::EncapsulationKey(..) | VerifyError::DecapsulationKey(..) => {
+                "derive_key_pair"
+            }
+            VerifyError::Ciphertext(..) | VerifyError::SharedSecretEncaps(..) => "encaps_derand",
+            VerifyError::SharedSecretDecaps(..) => "decaps",
+            VerifyError::InputLength { operation, .. } => operation,
+        }
+    }
+
+    /// The value computed by this implementation, for a mismatch
+    pub fn actual(&self) -> Option<&[u8]> {
+        self.values().map(|(actual, _)| actual)
+    }
+
+    /// The value in the vector, for a mismatch
+    pub fn expected(&self) -> Option<&[u8]> {
+        self.values().map(|(_, expected)| expected)
+    }
+
+    /// For a mismatch, the offset of the first byte that differs, or the length of the shorter
+    /// value if one is a prefix of the other
+    pub fn first_difference(&self) -> Option<usize> {
+        let (actual, expected) = self.values()?;
+        Some(
+            actual
+                .iter()
+                .zip(expected)
+                .position(|(a, b)| a != b)
+                .unwrap_or(actual.len().min(expected.len())),
+        )
+    }
+
+    fn values(&self) -> Option<(&[u8], &[u8])> {
+        match self {
+            VerifyError::EncapsulationKey(actual, expected)
+            | VerifyError::DecapsulationKey(actual, expected)
+            | VerifyError::Ciphertext(actual, expected)
+            | VerifyError::SharedSecretEncaps(actual, expected)
+            | VerifyError::SharedSecretDecaps(actual, expected) => Some((actual, expected)),
+            VerifyError::InputLength { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::InputLength {
+                field,
+                operation,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} for {} has {} bytes, expected {}",
+                field, operation, actual, expected
+            ),
+            _ => write!(
+                f,
+                "{} from {} differs at byte {}: expected {}, got {}",
+                self.field(),
+                self.operation(),
+                self.first_difference().unwrap_or_default(),
+                hex::encode(self.expected().unwrap_or_default()),
+                hex::encode(self.actual().unwrap_or_default())
+            ),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
 /// Test vector for a hybrid KEM instance
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct HybridKemTestVector {
     #[serde(with = "hex::serde")]
     pub seed: Vec<u8>,
@@ -44,6 +146,11 @@ impl HybridKemTestVector {
     pub fn generate<K: HybridKem>(index: u8) -> Self {
         let seed = vec![index; K::SEED_SIZE];
         let randomness = vec![index.wrapping_add(100); K::RANDOMNESS_SIZE];
+        Self::derive::<K>(seed, randomness)
+    }
+
+    /// Compute the vector for the given key generation seed and encapsulation randomness
+    pub fn derive<K: HybridKem>(seed: Vec<u8>, randomness: Vec<u8>) -> Self {
         let (dk, ek, info) = K::derive_key_pair(&seed);
         let (ct, ss) = K::encaps_derand(&ek, &randomness);
 
@@ -60,6 +167,27 @@ impl HybridKemTestVector {
     }
 
     pub fn verify<K: HybridKem>(&self) -> Result<(), VerifyError> {
+        // The seed and randomness are the only inputs used; the other fields are only compared
+        let inputs = [
+            ("seed", "derive_key_pair", &self.seed, K::SEED_SIZE),
+            (
+                "randomness",
+                "encaps_derand",
+                &self.randomness,
+                K::RANDOMNESS_SIZE,
+            ),
+        ];
+        for (field, operation, value, expected) in inputs {
+            if value.len() != expected {
+                return Err(VerifyError::InputLength {
+                    field,
+                    operation,
+                    expected,
+                    actual: value.len(),
+                });
+            }
+        }
+
         // Verify deterministic key generation
         let (dk, ek, _) = K::derive_key_pair(&self.seed);
 
@@ -71,9 +199,9 @@ impl HybridKemTestVector {
         }
 
         if ek != self.encapsulation_key {
-            return Err(VerifyError::DecapsulationKey(
-                dk,
-                self.decapsulation_key.clone(),
+            return Err(VerifyError::EncapsulationKey(
+                ek,
+                self.encapsulation_key.clone(),
             ));
         }
 
@@ -105,10 +233,486 @@ impl HybridKemTestVector {
     }
 }
 
-/// Complete test vector collection for all hybrid KEM instances
-#[derive(Debug, Serialize, Deserialize)]
+/// How the seed and randomness of generated vectors are chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seeding {
+    /// `seed = [i; Nseed]` and `randomness = [i + 100; ...]` for the `i`-th vector
+    Index,
+    /// The seed and randomness of each vector in turn are read from the output of SHAKE128 on
+    /// the empty string, as in the files in `spec/`
+    Shake128,
+}
+
+/// Generate `count` vectors for `K`.  With `Seeding::Index`, `count` is at most 256.
+pub fn generate<K: HybridKem>(count: usize, seeding: Seeding) -> Vec<HybridKemTestVector> {
+    match seeding {
+        Seeding::Index => {
+            assert!(count <= 256, "Index seeding supports at most 256 vectors");
+            (0..count)
+                .map(|i| HybridKemTestVector::generate::<K>(i as u8))
+                .collect()
+        }
+        Seeding::Shake128 => {
+            let mut reader = sha3::Shake128::default().finalize_xof();
+            (0..count)
+                .map(|_| {
+                    let mut seed = vec![0u8; K::SEED_SIZE];
+                    let mut randomness = vec![0u8; K::RANDOMNESS_SIZE];
+                    reader.read(&mut seed);
+                    reader.read(&mut randomness);
+                    HybridKemTestVector::derive::<K>(seed, randomness)
+                })
+                .collect()
+        }
+    }
+}
+
+/// Complete test vector collection for all hybrid KEM instances.  Instances without vectors are
+/// omitted from the JSON.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TestVectors {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub mlkem768_p256: Vec<HybridKemTestVector>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub mlkem768_x25519: Vec<HybridKemTestVector>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub mlkem1024_p384: Vec<HybridKemTestVector>,
 }
+
+impl TestVectors {
+    /// Generate `count` vectors for each of `algorithms`
+    pub fn generate(algorithms: &[Algorithm], count: usize, seeding: Seeding) -> Self {
+        let mut test_vectors = TestVectors::default();
+        for &algorithm in algorithms {
+            *test_vectors.get_mut(algorithm) = match algorithm {
+                Algorithm::MlKem768P256 => generate::<MlKem768P256>(count, seeding),
+                Algorithm::MlKem768X25519 => generate::<MlKem768X25519>(count, seeding),
+                Algorithm::MlKem1024P384 => generate::<MlKem1024P384>(count, seeding),
+            };
+        }
+        test_vectors
+    }
+
+    /// The vectors for `algorithm`
+    pub fn get(&self, algorithm: Algorithm) -> &Vec<HybridKemTestVector> {
+        match algorithm {
+            Algorithm::MlKem768P256 => &self.mlkem768_p256,
+            Algorithm::MlKem768X25519 => &self.mlkem768_x25519,
+            Algorithm::MlKem1024P384 => &self.mlkem1024_p384,
+        }
+    }
+
+    /// Verify each of the vectors for `algorithm`
+    pub fn verify(&self, algorithm: Algorithm) -> Vec<Result<(), VerifyError>> {
+        let vectors = self.get(algorithm);
+        match algorithm {
+            Algorithm::MlKem768P256 => vectors.iter().map(|v| v.verify::<MlKem768P256>()).collect(),
+            Algorithm::MlKem768X25519 => vectors
+                .iter()
+                .map(|v| v.verify::<MlKem768X25519>())
+                .collect(),
+            Algorithm::MlKem1024P384 => vectors
+                .iter()
+                .map(|v| v.verify::<MlKem1024P384>())
+                .collect(),
+        }
+    }
+
+    pub fn get_mut(&mut self, algorithm: Algorithm) -> &mut Vec<HybridKemTestVector> {
+        match algorithm {
+            Algorithm::MlKem768P256 => &mut self.mlkem768_p256,
+            Algorithm::MlKem768X25519 => &mut self.mlkem768_x25519,
+            Algorithm::MlKem1024P384 => &mut self.mlkem1024_p384,
+        }
+    }
+
+    /// Render as the markdown included in the draft, with a section for each instance that has
+    /// vectors
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+        for algorithm in Algorithm::ALL {
+            let vectors = self.get(algorithm);
+            if vectors.is_empty() {
+                continue;
+            }
+
+            md += &format!("## {}\n\n", algorithm.name());
+            for vector in vectors {
+                md += "~~~\n";
+                for (label, value) in [
+                    ("seed", &vector.seed),
+                    ("randomness", &vector.randomness),
+                    ("encapsulation_key", &vector.encapsulation_key),
+                    ("decapsulation_key", &vector.decapsulation_key),
+                    ("decapsulation_key_pq", &vector.decapsulation_key_pq),
+                    ("decapsulation_key_t", &vector.decapsulation_key_t),
+                    ("ciphertext", &vector.ciphertext),
+                    ("shared_secret", &vector.shared_secret),
+                ] {
+                    md += &format_markdown_field(label, &hex::encode(value));
+                    md += "\n";
+                }
+                md += "~~~\n\n";
+            }
+        }
+        md
+    }
+}
+
+/// Format `label = hex`, wrapping at 64 columns with continuation lines aligned after the `=`
+fn format_markdown_field(label: &str, hex_str: &str) -> String {
+    let prefix = format!("{} = ", label);
+    let indent = " ".repeat(prefix.len());
+
+    if hex_str.len() + prefix.len() <= 64 {
+        // Fits on one line
+        format!("{}{}", prefix, hex_str)
+    } else {
+        // Need to wrap
+        let mut result = prefix;
+        let mut remaining = hex_str;
+        let first_line_len = 64 - result.len();
+
+        // First line
+        result.push_str(&remaining[..first_line_len]);
+        remaining = &remaining[first_line_len..];
+
+        // Subsequent lines
+        while !remaining.is_empty() {
+            result.push('\n');
+            result.push_str(&indent);
+            let line_len = std::cmp::min(remaining.len(), 64 - indent.len());
+            result.push_str(&remaining[..line_len]);
+            remaining = &remaining[line_len..];
+        }
+
+        result
+    }
+}
+
+/// Format a field of the `spec/` text format: values of up to 36 bytes follow the name on the
+/// same line, and longer values are wrapped at 72 hex digits on indented lines
+fn format_spec_field(name: &str, value: &[u8]) -> String {
+    let hex_str = hex::encode(value);
+    if hex_str.len() <= 72 {
+        return format!("{}     {}\n", name, hex_str);
+    }
+
+    let mut result = format!("{}\n", name);
+    for line in hex_str.as_bytes().chunks(72) {
+        result += "  ";
+        result += std::str::from_utf8(line).unwrap();
+        result += "\n";
+    }
+    result
+}
+
+/// The name of the file in `spec/` holding the vectors for `algorithm`
+pub fn spec_filename(algorithm: Algorithm) -> String {
+    format!("test-vectors-{}.txt", algorithm.composition())
+}
+
+/// Render vectors in the text format of the files in `spec/`
+pub fn to_spec_text(vectors: &[HybridKemTestVector]) -> String {
+    let mut text = String::new();
+    for vector in vectors {
+        text += &format_spec_field("seed", &vector.seed);
+        text += &format_spec_field("sk", &vector.decapsulation_key);
+        text += &format_spec_field("pk", &vector.encapsulation_key);
+        text += &format_spec_field("randomness", &vector.randomness);
+        text += &format_spec_field("ct", &vector.ciphertext);
+        text += &format_spec_field("ss", &vector.shared_secret);
+        text += "\n";
+    }
+    text
+}
+
+/// The fields of a vector in the `spec/` text format, in order
+const SPEC_FIELDS: [&str; 6] = ["seed", "sk", "pk", "randomness", "ct", "ss"];
+
+/// An error parsing the `spec/` text format, with the line on which it was found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpecTextError {
+    pub line: usize,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for SpecTextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for SpecTextError {}
+
+/// Parse vectors in the text format of the files in `spec/`, as written by `to_spec_text`.  The
+/// format does not include the component decapsulation keys, so those fields are left empty.
+pub fn from_spec_text(text: &str) -> Result<Vec<HybridKemTestVector>, SpecTextError> {
+    let mut vectors = Vec::new();
+    let mut fields: Vec<String> = Vec::new();
+    // Whether the value of the last field is wrapped onto the following indented lines
+    let mut wrapped = false;
+
+    let mut line_number = 0;
+    for line in text.lines() {
+        line_number += 1;
+        let error = |reason| SpecTextError {
+            line: line_number,
+            reason,
+        };
+
+        if line.is_empty() {
+            if fields.len() != SPEC_FIELDS.len() {
+                return Err(error("incomplete vector"));
+            }
+
+            let values = fields
+                .iter()
+                .map(hex::decode)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| error("invalid hex value"))?;
+            let [seed, sk, pk, randomness, ct, ss] = <[Vec<u8>; 6]>::try_from(values).unwrap();
+            vectors.push(HybridKemTestVector {
+                seed,
+                randomness,
+                encapsulation_key: pk,
+                decapsulation_key: sk,
+                decapsulation_key_pq: Vec::new(),
+                decapsulation_key_t: Vec::new(),
+                ciphertext: ct,
+                shared_secret: ss,
+            });
+            fields.clear();
+            wrapped = false;
+        } else if let Some(hex) = line.strip_prefix("  ") {
+            if !wrapped {
+                return Err(error("unexpected indented line"));
+            }
+            fields.last_mut().unwrap().push_str(hex);
+        } else {
+            let Some(&expected) = SPEC_FIELDS.get(fields.len()) else {
+                return Err(error("expected a blank line after the vector"));
+            };
+
+            match line.split_once("     ") {
+                Some((name, hex)) if name == expected => {
+                    fields.push(hex.to_string());
+                    wrapped = false;
+                }
+                None if line == expected => {
+                    fields.push(String::new());
+                    wrapped = true;
+                }
+                _ => return Err(error("unexpected field")),
+            }
+        }
+    }
+
+    if !fields.is_empty() {
+        return Err(SpecTextError {
+            line: line_number,
+            reason: "incomplete vector",
+        });
+    }
+
+    Ok(vectors)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::kem::EncapsDerand;
+
+    #[test]
+    fn index_seeding() {
+        let vectors = generate::<MlKem768X25519>(3, Seeding::Index);
+        assert_eq!(vectors.len(), 3);
+        for (i, v) in vectors.iter().enumerate() {
+            assert_eq!(v.seed, vec![i as u8; 32]);
+            assert!(v.verify::<MlKem768X25519>().is_ok());
+        }
+    }
+
+    #[test]
+    fn shake128_seeding() {
+        let vectors = generate::<MlKem768P256>(2, Seeding::Shake128);
+        for v in &vectors {
+            assert!(v.verify::<MlKem768P256>().is_ok());
+        }
+
+        // The stream starts with the first seed in `spec/`
+        assert_eq!(
+            hex::encode(&vectors[0].seed),
+            "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26"
+        );
+        assert_eq!(vectors[0].randomness.len(), MlKem768P256::RANDOMNESS_SIZE);
+        assert_ne!(vectors[0].seed, vectors[1].seed);
+    }
+
+    #[test]
+    fn verify_errors() {
+        let vector = HybridKemTestVector::generate::<MlKem768P256>(0);
+        assert_eq!(vector.verify::<MlKem768P256>(), Ok(()));
+
+        let mut bad = vector.clone();
+        bad.encapsulation_key[5] ^= 0xff;
+        let err = bad.verify::<MlKem768P256>().unwrap_err();
+        assert_eq!(
+            err,
+            VerifyError::EncapsulationKey(
+                vector.encapsulation_key.clone(),
+                bad.encapsulation_key.clone()
+            )
+        );
+        assert_eq!(err.field(), "encapsulation_key");
+        assert_eq!(err.operation(), "derive_key_pair");
+        assert_eq!(err.first_difference(), Some(5));
+        assert!(err
+            .to_string()
+            .starts_with("encapsulation_key from derive_key_pair differs at byte 5: expected "));
+
+        let mut bad = vector.clone();
+        bad.ciphertext.truncate(10);
+        let err = bad.verify::<MlKem768P256>().unwrap_err();
+        assert_eq!(err.field(), "ciphertext");
+        assert_eq!(err.first_difference(), Some(10));
+        assert_eq!(err.expected(), Some(&vector.ciphertext[..10]));
+        assert_eq!(err.actual(), Some(&vector.ciphertext[..]));
+
+        let mut bad = vector.clone();
+        bad.shared_secret[31] ^= 1;
+        let err = bad.verify::<MlKem768P256>().unwrap_err();
+        assert!(matches!(err, VerifyError::SharedSecretEncaps(..)));
+        assert_eq!(err.first_difference(), Some(31));
+
+        // Inputs of the wrong length are reported rather than passed on to the KEM
+        let mut short = vector.clone();
+        short.seed.pop();
+        let err = short.verify::<MlKem768P256>().unwrap_err();
+        assert_eq!(
+            err,
+            VerifyError::InputLength {
+                field: "seed",
+                operation: "derive_key_pair",
+                expected: 32,
+                actual: 31,
+            }
+        );
+        assert_eq!(err.first_difference(), None);
+        assert_eq!(
+            err.to_string(),
+            "seed for derive_key_pair has 31 bytes, expected 32"
+        );
+
+        let mut long = vector.clone();
+        long.randomness.push(0);
+        let err = long.verify::<MlKem768P256>().unwrap_err();
+        assert_eq!(err.field(), "randomness");
+        assert_eq!(err.operation(), "encaps_derand");
+
+        let test_vectors = TestVectors {
+            mlkem768_p256: vec![vector, bad],
+            ..Default::default()
+        };
+        let results = test_vectors.verify(Algorithm::MlKem768P256);
+        assert!(results[0].is_ok() && results[1].is_err());
+        assert!(test_vectors.verify(Algorithm::MlKem768X25519).is_empty());
+    }
+
+    #[test]
+    fn filter() {
+        let test_vectors = TestVectors::generate(&[Algorithm::MlKem1024P384], 1, Seeding::Index);
+        assert!(test_vectors.mlkem768_p256.is_empty());
+        assert_eq!(test_vectors.mlkem1024_p384.len(), 1);
+
+        let json = serde_json::to_string(&test_vectors).unwrap();
+        assert!(json.starts_with("{\"mlkem1024_p384\":"));
+        let parsed: TestVectors = serde_json::from_str(&json).unwrap();
+        assert!(parsed.mlkem768_x25519.is_empty());
+        assert!(test_vectors
+            .to_markdown()
+            .starts_with("## MLKEM1024-P384\n\n~~~\n"));
+    }
+
+    #[test]
+    fn spec_text() {
+        let vectors = generate::<MlKem768P256>(1, Seeding::Shake128);
+        let text = to_spec_text(&vectors);
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            format!("seed     {}", hex::encode(&vectors[0].seed))
+        );
+        assert_eq!(
+            lines[1],
+            format!("sk     {}", hex::encode(&vectors[0].seed))
+        );
+        assert_eq!(lines[2], "pk");
+        assert!(lines[3].starts_with("  ") && lines[3].len() == 2 + 72);
+        assert!(lines.iter().all(|l| l.len() <= 2 + 72));
+        assert!(text.ends_with("\n\n"));
+
+        let pk: String = lines[3..]
+            .iter()
+            .take_while(|l| l.starts_with("  "))
+            .map(|l| l.trim())
+            .collect();
+        assert_eq!(pk, hex::encode(&vectors[0].encapsulation_key));
+    }
+
+    // The files in `spec/` are up to date, as written by `update_test_vectors.sh`
+    #[test]
+    fn checked_in_spec_files() {
+        let spec = std::path::Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../spec"));
+        let test_vectors = TestVectors::generate(&Algorithm::ALL, 3, Seeding::Shake128);
+        for algorithm in Algorithm::ALL {
+            let path = spec.join(spec_filename(algorithm));
+            let text = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(
+                text,
+                to_spec_text(test_vectors.get(algorithm)),
+                "{:?}",
+                path
+            );
+            assert_eq!(to_spec_text(&from_spec_text(&text).unwrap()), text);
+        }
+    }
+
+    #[test]
+    fn parse_spec_text() {
+        let mut vectors = generate::<MlKem768X25519>(2, Seeding::Shake128);
+        for v in &mut vectors {
+            v.decapsulation_key_pq.clear();
+            v.decapsulation_key_t.clear();
+        }
+
+        let text = to_spec_text(&vectors);
+        assert_eq!(from_spec_text(&text), Ok(vectors));
+        assert_eq!(from_spec_text(""), Ok(vec![]));
+
+        // Values may be wrapped or not, whatever their length
+        let text = "seed\n  0001\n  02\nsk     \npk     03\nrandomness     \nct     \nss     04\n\n";
+        let parsed = from_spec_text(text).unwrap();
+        assert_eq!(parsed[0].seed, vec![0, 1, 2]);
+        assert_eq!(parsed[0].decapsulation_key, Vec::<u8>::new());
+        assert_eq!(parsed[0].shared_secret, vec![4]);
+
+        let error = |line, reason| Err(SpecTextError { line, reason });
+        assert_eq!(from_spec_text("seed     00\n"), error(1, "incomplete vector"));
+        assert_eq!(from_spec_text("sk     00\n"), error(1, "unexpected field"));
+        assert_eq!(
+            from_spec_text("seed     00\n  01\n"),
+            error(2, "unexpected indented line")
+        );
+        assert_eq!(from_spec_text("\n"), error(1, "incomplete vector"));
+
+        let text = "seed     0g\nsk     \npk     \nrandomness     \nct     \nss     \n\n";
+        assert_eq!(from_spec_text(text), error(7, "invalid hex value"));
+        let text = text.replace("0g", "00").replace("\n\n", "\nss     \n");
+        assert_eq!(
+            from_spec_text(&text),
+            error(7, "expected a blank line after the vector")
+        );
+    }
+}
@@ -0,0 +1,409 @@
+//! Statistical tests for secret-dependent timing
+//!
+//! This follows dudect ("dude, is my code constant time?"): an operation is timed on many inputs
+//! drawn at random from two classes, a fixed input and random inputs, and Welch's t-test checks
+//! whether the two timing distributions have the same mean.  A large |t| means that the timing
+//! depends on the input.  Timing uses the monotonic clock, so no cycle counters or other special
+//! hardware are needed, but the results are only meaningful for optimized builds on a quiet
+//! machine.
+//!
+//! Measurements have a long upper tail from interrupts and scheduling, so as in dudect, the test
+//! is repeated on the measurements below several percentiles, and the largest |t| is reported.
+//! dudect treats |t| above 10 as a definite leak, and |t| above 4.5 as a likely one.
+
+use crate::group::{NominalGroup, P256, P384, X25519};
+use crate::hybrid::HybridKem;
+use crate::kem::{MlKem768, MlKem1024, PqKem};
+use crate::{MlKem768P256, MlKem768X25519, MlKem1024P384};
+use rand::{CryptoRng, Rng};
+use std::fmt;
+use std::hint::black_box;
+use std::time::Instant;
+
+/// The |t| above which a target is reported as leaking
+pub const DEFAULT_THRESHOLD: f64 = 10.0;
+
+/// The percentiles at which measurements are cropped, besides testing all of them
+const PERCENTILES: [f64; 5] = [0.5, 0.75, 0.9, 0.95, 0.99];
+
+/// Runs of the operation before timing starts, to warm up caches and branch predictors
+const WARMUP: usize = 100;
+
+/// The class of an input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Class {
+    Fixed,
+    Random,
+}
+
+/// The time taken by one run of the operation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sample {
+    pub class: Class,
+    pub nanos: u64,
+}
+
+/// Running mean and variance, using Welford's algorithm
+#[derive(Clone, Copy, Debug, Default)]
+struct Moments {
+    n: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl Moments {
+    fn push(&mut self, x: f64) {
+        self.n += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.n;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn variance(&self) -> f64 {
+        self.m2 / (self.n - 1.0)
+    }
+}
+
+/// Welch's t-test between the two classes
+#[derive(Clone, Debug, Default)]
+pub struct WelchTest {
+    fixed: Moments,
+    random: Moments,
+}
+
+impl WelchTest {
+    pub fn push(&mut self, class: Class, x: f64) {
+        match class {
+            Class::Fixed => self.fixed.push(x),
+            Class::Random => self.random.push(x),
+        }
+    }
+
+    /// The t statistic, which is zero until each class has two values
+    pub fn t(&self) -> f64 {
+        if self.fixed.n < 2.0 || self.random.n < 2.0 {
+            return 0.0;
+        }
+
+        let difference = self.fixed.mean - self.random.mean;
+        let error =
+            (self.fixed.variance() / self.fixed.n + self.random.variance() / self.random.n).sqrt();
+        match (difference == 0.0, error == 0.0) {
+            (true, _) => 0.0,
+            (false, true) => difference.signum() * f64::INFINITY,
+            (false, false) => difference / error,
+        }
+    }
+}
+
+/// Time `operation` on `measurements` inputs, each made by `input` for a class chosen at random
+pub fn measure<I, O>(
+    measurements: usize,
+    rng: &mut dyn CryptoRng,
+    mut input: impl FnMut(Class, &mut dyn CryptoRng) -> I,
+    mut operation: impl FnMut(&I) -> O,
+) -> Vec<Sample> {
+    // Make all of the inputs first, so that only the operation is timed
+    let inputs: Vec<(Class, I)> = (0..measurements)
+        .map(|_| {
+            let class = if rng.random() {
+                Class::Fixed
+            } else {
+                Class::Random
+            };
+            (class, input(class, &mut *rng))
+        })
+        .collect();
+
+    for (_, input) in inputs.iter().take(WARMUP) {
+        black_box(operation(black_box(input)));
+    }
+
+    inputs
+        .iter()
+        .map(|(class, input)| {
+            let start = Instant::now();
+            let output = operation(black_box(input));
+            let nanos = start.elapsed().as_nanos() as u64;
+            black_box(output);
+            Sample {
+                class: *class,
+                nanos,
+            }
+        })
+        .collect()
+}
+
+/// The largest |t| over all of the samples and the samples cropped at each of the percentiles
+pub fn max_t(samples: &[Sample]) -> f64 {
+    let mut sorted: Vec<u64> = samples.iter().map(|sample| sample.nanos).collect();
+    sorted.sort_unstable();
+    if sorted.is_empty() {
+        return 0.0;
+    }
+
+    let mut cutoffs = vec![u64::MAX];
+    for percentile in PERCENTILES {
+        cutoffs.push(sorted[((sorted.len() - 1) as f64 * percentile) as usize]);
+    }
+
+    cutoffs
+        .into_iter()
+        .map(|cutoff| {
+            let mut test = WelchTest::default();
+            for sample in samples.iter().filter(|sample| sample.nanos <= cutoff) {
+                test.push(sample.class, sample.nanos as f64);
+            }
+            test.t().abs()
+        })
+        .fold(0.0, f64::max)
+}
+
+fn random_bytes(rng: &mut dyn CryptoRng, len: usize) -> Vec<u8> {
+    let mut bytes = vec![0; len];
+    rng.fill_bytes(&mut bytes);
+    bytes
+}
+
+/// `decaps` of a fixed valid ciphertext, against copies with a random byte of `ct_pq` changed,
+/// which take ML-KEM's implicit rejection path.  `ct_t` is left alone, since almost any change to
+/// it fails `NominalGroup::validate_element`, on which `decaps` panics.
+fn decaps<H: HybridKem, PQ: PqKem>(measurements: usize, rng: &mut dyn CryptoRng) -> Vec<Sample> {
+    let (dk, ek, _) = H::derive_key_pair(&random_bytes(rng, H::SEED_SIZE));
+    let (ct, _) = H::encaps_derand(&ek, &random_bytes(rng, H::RANDOMNESS_SIZE));
+
+    let input = |class, rng: &mut dyn CryptoRng| {
+        let mut ct = ct.clone();
+        if class == Class::Random {
+            ct[rng.random_range(..PQ::CIPHERTEXT_SIZE)] ^= rng.random_range(1..=u8::MAX);
+        }
+        ct
+    };
+    measure(measurements, rng, input, |ct| H::decaps(&dk, ct))
+}
+
+/// `exp` of a fixed element by a fixed scalar, against random scalars
+fn exp<G: NominalGroup>(measurements: usize, rng: &mut dyn CryptoRng) -> Vec<Sample> {
+    let element = G::exp(
+        &G::generator(),
+        &G::random_scalar(&random_bytes(rng, G::SEED_SIZE)),
+    );
+    let fixed = G::random_scalar(&random_bytes(rng, G::SEED_SIZE));
+
+    let input = |class, rng: &mut dyn CryptoRng| match class {
+        Class::Fixed => fixed.clone(),
+        Class::Random => G::random_scalar(&random_bytes(rng, G::SEED_SIZE)),
+    };
+    measure(measurements, rng, input, |scalar| G::exp(&element, scalar))
+}
+
+/// `random_scalar` from a fixed seed, against random seeds.  On the NIST curves, this is a
+/// rejection sampling loop.
+fn random_scalar<G: NominalGroup>(measurements: usize, rng: &mut dyn CryptoRng) -> Vec<Sample> {
+    let fixed = random_bytes(rng, G::SEED_SIZE);
+
+    let input = |class, rng: &mut dyn CryptoRng| match class {
+        Class::Fixed => fixed.clone(),
+        Class::Random => random_bytes(rng, G::SEED_SIZE),
+    };
+    measure(measurements, rng, input, |seed| G::random_scalar(seed))
+}
+
+/// `element_to_shared_secret` of a fixed element, against random elements
+fn element_to_shared_secret<G: NominalGroup>(
+    measurements: usize,
+    rng: &mut dyn CryptoRng,
+) -> Vec<Sample> {
+    let random_element = |rng: &mut dyn CryptoRng| {
+        G::exp(
+            &G::generator(),
+            &G::random_scalar(&random_bytes(rng, G::SEED_SIZE)),
+        )
+    };
+    let fixed = random_element(rng);
+
+    let input = |class, rng: &mut dyn CryptoRng| match class {
+        Class::Fixed => fixed.clone(),
+        Class::Random => random_element(rng),
+    };
+    measure(measurements, rng, input, |element| {
+        G::element_to_shared_secret(element)
+    })
+}
+
+/// An operation to test, which measures itself on inputs of both classes
+pub struct Target {
+    pub name: &'static str,
+    pub measure: fn(usize, &mut dyn CryptoRng) -> Vec<Sample>,
+}
+
+/// The operations that handle secrets
+pub const TARGETS: &[Target] = &[
+    Target {
+        name: "MLKEM768-P256 decaps (valid vs tampered)",
+        measure: decaps::<MlKem768P256, MlKem768>,
+    },
+    Target {
+        name: "MLKEM768-X25519 decaps (valid vs tampered)",
+        measure: decaps::<MlKem768X25519, MlKem768>,
+    },
+    Target {
+        name: "MLKEM1024-P384 decaps (valid vs tampered)",
+        measure: decaps::<MlKem1024P384, MlKem1024>,
+    },
+    Target {
+        name: "P256 exp",
+        measure: exp::<P256>,
+    },
+    Target {
+        name: "P256 random_scalar",
+        measure: random_scalar::<P256>,
+    },
+    Target {
+        name: "P256 element_to_shared_secret",
+        measure: element_to_shared_secret::<P256>,
+    },
+    Target {
+        name: "P384 exp",
+        measure: exp::<P384>,
+    },
+    Target {
+        name: "P384 random_scalar",
+        measure: random_scalar::<P384>,
+    },
+    Target {
+        name: "P384 element_to_shared_secret",
+        measure: element_to_shared_secret::<P384>,
+    },
+    Target {
+        name: "X25519 exp",
+        measure: exp::<X25519>,
+    },
+    Target {
+        name: "X25519 random_scalar",
+        measure: random_scalar::<X25519>,
+    },
+    Target {
+        name: "X25519 element_to_shared_secret",
+        measure: element_to_shared_secret::<X25519>,
+    },
+];
+
+/// The result of testing one target
+#[derive(Clone, Debug)]
+pub struct Outcome {
+    pub name: &'static str,
+    pub measurements: usize,
+    pub t: f64,
+    pub threshold: f64,
+}
+
+impl Outcome {
+    pub fn passed(&self) -> bool {
+        self.t <= self.threshold
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {:<45} n = {:<8} max |t| = {:>8.2}",
+            if self.passed() { "✅" } else { "❌" },
+            self.name,
+            self.measurements,
+            self.t
+        )
+    }
+}
+
+impl Target {
+    pub fn run(&self, measurements: usize, threshold: f64, rng: &mut dyn CryptoRng) -> Outcome {
+        let samples = (self.measure)(measurements, rng);
+        Outcome {
+            name: self.name,
+            measurements,
+            t: max_t(&samples),
+            threshold,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn welch_t() {
+        let mut test = WelchTest::default();
+        assert_eq!(test.t(), 0.0);
+
+        for x in [1.0, 2.0, 3.0, 4.0] {
+            test.push(Class::Fixed, x);
+        }
+        for x in [2.0, 4.0, 6.0, 8.0] {
+            test.push(Class::Random, x);
+        }
+
+        // Means 2.5 and 5, variances 5/3 and 20/3
+        let expected = -2.5 / (25.0f64 / 12.0).sqrt();
+        assert!((test.t() - expected).abs() < 1e-12);
+
+        // Identical constant classes give zero rather than NaN
+        let mut test = WelchTest::default();
+        for _ in 0..4 {
+            test.push(Class::Fixed, 7.0);
+            test.push(Class::Random, 7.0);
+        }
+        assert_eq!(test.t(), 0.0);
+    }
+
+    #[test]
+    fn outliers() {
+        // The classes only differ in outliers above the 99th percentile
+        let mut samples: Vec<Sample> = (0..1000)
+            .map(|i| Sample {
+                class: if i % 2 == 0 {
+                    Class::Fixed
+                } else {
+                    Class::Random
+                },
+                nanos: 100 + i % 7,
+            })
+            .collect();
+        assert!(max_t(&samples) < 1.0);
+
+        for sample in samples.iter_mut().step_by(200) {
+            sample.nanos = 1_000_000;
+        }
+        assert!(max_t(&samples) > 1.0);
+        assert_eq!(max_t(&[]), 0.0);
+    }
+
+    #[test]
+    fn detects_leak() {
+        // Work that only the random class does is far above any threshold
+        let mut rng = rand::rng();
+        let samples = measure(
+            1000,
+            &mut rng,
+            |class, _| class,
+            |class| {
+                let rounds = if *class == Class::Random { 10_000 } else { 0 };
+                (0..rounds).fold(0u64, |acc, i| black_box(acc ^ i))
+            },
+        );
+        assert_eq!(samples.len(), 1000);
+        assert!(max_t(&samples) > DEFAULT_THRESHOLD);
+    }
+
+    #[test]
+    fn targets() {
+        let mut rng = rand::rng();
+        for target in TARGETS {
+            let outcome = target.run(10, DEFAULT_THRESHOLD, &mut rng);
+            assert_eq!(outcome.measurements, 10);
+            assert!(!outcome.t.is_nan());
+        }
+    }
+}
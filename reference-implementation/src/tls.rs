@@ -0,0 +1,331 @@
+//! TLS 1.3 hybrid key exchange groups
+//!
+//! The hybrid groups registered for TLS 1.3 (X25519MLKEM768, SecP256r1MLKEM768) are built from
+//! the same components as the hybrid KEMs in this crate, but they are not instances of the
+//! frameworks in `hybrid`:
+//!
+//! * The key shares are a plain concatenation of the component values, and the order of the
+//!   components depends on the group (ML-KEM first for X25519MLKEM768, ECDH first for
+//!   SecP256r1MLKEM768).
+//! * The shared secret is the concatenation of the component shared secrets, in the same order
+//!   as the key shares, rather than the output of a KDF over a combiner input.
+//!
+//! We model each group as a KEM: the client key share is the encapsulation key, the server key
+//! share is the ciphertext, and the TLS shared secret is the KEM shared secret.
+
+use crate::group::*;
+use crate::hybrid::{HybridSubKeys, split};
+use crate::kem::*;
+use rand::{CryptoRng, Rng};
+
+pub trait TlsGroupConstants {
+    /// The NamedGroup code point registered for the group
+    const CODEPOINT: u16;
+
+    /// Whether the PQ component precedes the traditional component on the wire
+    const PQ_FIRST: bool;
+}
+
+/// Concatenate PQ and T values in the order used by the group
+fn join<C: TlsGroupConstants>(pq: &[u8], t: &[u8]) -> Vec<u8> {
+    if C::PQ_FIRST {
+        [pq, t].concat()
+    } else {
+        [t, pq].concat()
+    }
+}
+
+/// Split a concatenation produced by `join` back into its (PQ, T) parts
+fn separate<C: TlsGroupConstants>(v: &[u8], pq_len: usize, t_len: usize) -> (Vec<u8>, Vec<u8>) {
+    if C::PQ_FIRST {
+        split(v, pq_len, t_len)
+    } else {
+        let (t, pq) = split(v, t_len, pq_len);
+        (pq, t)
+    }
+}
+
+fn client_key_share<T: NominalGroup, C: TlsGroupConstants>(
+    ek_pq: &EncapsulationKey,
+    dk_t: &Scalar,
+) -> EncapsulationKey {
+    let ek_t = T::exp(&T::generator(), dk_t);
+    join::<C>(ek_pq, &ek_t)
+}
+
+fn server_key_share<T: NominalGroup, C: TlsGroupConstants>(
+    ct_pq: &Ciphertext,
+    ss_pq: &SharedSecret,
+    ek_t: &Element,
+    sk_e: &Scalar,
+) -> (Ciphertext, SharedSecret) {
+    let ct_t = T::exp(&T::generator(), sk_e);
+    let ss_t = T::element_to_shared_secret(&T::exp(ek_t, sk_e));
+    (join::<C>(ct_pq, &ct_t), join::<C>(ss_pq, &ss_t))
+}
+
+fn client_shared_secret<PQ: PqKem, T: NominalGroup, C: TlsGroupConstants>(
+    dk_pq: &DecapsulationKey,
+    dk_t: &Scalar,
+    ct: &Ciphertext,
+) -> SharedSecret {
+    let (ct_pq, ct_t) = separate::<C>(ct, PQ::CIPHERTEXT_SIZE, T::ELEMENT_SIZE);
+    let ss_pq = PQ::decaps(dk_pq, &ct_pq);
+    let ss_t = T::element_to_shared_secret(&T::exp(&ct_t, dk_t));
+    join::<C>(&ss_pq, &ss_t)
+}
+
+/// Derive the component keys from a seed of the form `seed_pq || seed_t`
+fn expand_decaps_key<PQ: PqKem, T: NominalGroup>(
+    seed: &[u8],
+) -> (DecapsulationKey, Scalar, EncapsulationKey) {
+    let (seed_pq, seed_t) = split(seed, PQ::SEED_SIZE, T::SEED_SIZE);
+    let (dk_pq, ek_pq, _key_info) = PQ::derive_key_pair(&seed_pq);
+    let dk_t = T::random_scalar(&seed_t);
+    (dk_pq, dk_t, ek_pq)
+}
+
+#[derive(Default)]
+pub struct TlsHybrid<PQ, T, C> {
+    _phantom: core::marker::PhantomData<(PQ, T, C)>,
+}
+
+impl<PQ, T, C> SeedSize for TlsHybrid<PQ, T, C>
+where
+    PQ: SeedSize,
+    T: SeedSize,
+{
+    const SEED_SIZE: usize = PQ::SEED_SIZE + T::SEED_SIZE;
+}
+
+impl<PQ, T, C> SharedSecretSize for TlsHybrid<PQ, T, C>
+where
+    PQ: SharedSecretSize,
+    T: SharedSecretSize,
+{
+    const SHARED_SECRET_SIZE: usize = PQ::SHARED_SECRET_SIZE + T::SHARED_SECRET_SIZE;
+}
+
+impl<PQ, T, C> Kem for TlsHybrid<PQ, T, C>
+where
+    PQ: PqKem,
+    T: NominalGroup,
+    C: TlsGroupConstants,
+{
+    const ENCAPSULATION_KEY_SIZE: usize = PQ::ENCAPSULATION_KEY_SIZE + T::ELEMENT_SIZE;
+    const DECAPSULATION_KEY_SIZE: usize = Self::SEED_SIZE;
+    const CIPHERTEXT_SIZE: usize = PQ::CIPHERTEXT_SIZE + T::ELEMENT_SIZE;
+
+    type KeyInfo = HybridSubKeys;
+
+    fn derive_key_pair(seed: &[u8]) -> (DecapsulationKey, EncapsulationKey, Self::KeyInfo) {
+        assert_eq!(seed.len(), Self::SEED_SIZE);
+        let (dk_pq, dk_t, ek_pq) = expand_decaps_key::<PQ, T>(seed);
+        let ek = client_key_share::<T, C>(&ek_pq, &dk_t);
+        (seed.to_vec(), ek, HybridSubKeys { dk_pq, dk_t })
+    }
+
+    fn encaps(ek: &EncapsulationKey, rng: &mut impl CryptoRng) -> (SharedSecret, Ciphertext) {
+        assert_eq!(ek.len(), Self::ENCAPSULATION_KEY_SIZE);
+        let (ek_pq, ek_t) = separate::<C>(ek, PQ::ENCAPSULATION_KEY_SIZE, T::ELEMENT_SIZE);
+        let (ss_pq, ct_pq) = PQ::encaps(&ek_pq, rng);
+
+        let mut seed_e = vec![0u8; T::SEED_SIZE];
+        rng.fill(seed_e.as_mut_slice());
+        let sk_e = T::random_scalar(&seed_e);
+
+        let (ct, ss) = server_key_share::<T, C>(&ct_pq, &ss_pq, &ek_t, &sk_e);
+        (ss, ct)
+    }
+
+    fn decaps(dk: &DecapsulationKey, ct: &Ciphertext) -> SharedSecret {
+        assert_eq!(dk.len(), Self::DECAPSULATION_KEY_SIZE);
+        assert_eq!(ct.len(), Self::CIPHERTEXT_SIZE);
+        let (dk_pq, dk_t, _ek_pq) = expand_decaps_key::<PQ, T>(dk);
+        client_shared_secret::<PQ, T, C>(&dk_pq, &dk_t, ct)
+    }
+}
+
+impl<PQ, T, C> EncapsDerand for TlsHybrid<PQ, T, C>
+where
+    PQ: PqKem + EncapsDerand,
+    T: NominalGroup,
+    C: TlsGroupConstants,
+{
+    const RANDOMNESS_SIZE: usize = PQ::RANDOMNESS_SIZE + T::SEED_SIZE;
+
+    fn encaps_derand(ek: &EncapsulationKey, randomness: &[u8]) -> (Ciphertext, SharedSecret) {
+        assert_eq!(ek.len(), Self::ENCAPSULATION_KEY_SIZE);
+        assert_eq!(randomness.len(), Self::RANDOMNESS_SIZE);
+
+        let (ek_pq, ek_t) = separate::<C>(ek, PQ::ENCAPSULATION_KEY_SIZE, T::ELEMENT_SIZE);
+        let (randomness_pq, seed_e) = split(randomness, PQ::RANDOMNESS_SIZE, T::SEED_SIZE);
+
+        let (ct_pq, ss_pq) = PQ::encaps_derand(&ek_pq, &randomness_pq);
+        let sk_e = T::random_scalar(&seed_e);
+
+        server_key_share::<T, C>(&ct_pq, &ss_pq, &ek_t, &sk_e)
+    }
+}
+
+// X25519MLKEM768
+pub struct X25519MlKem768Constants;
+
+impl TlsGroupConstants for X25519MlKem768Constants {
+    const CODEPOINT: u16 = 0x11ec;
+    const PQ_FIRST: bool = true;
+}
+
+pub type X25519MlKem768 = TlsHybrid<MlKem768, X25519, X25519MlKem768Constants>;
+
+// SecP256r1MLKEM768
+pub struct SecP256r1MlKem768Constants;
+
+impl TlsGroupConstants for SecP256r1MlKem768Constants {
+    const CODEPOINT: u16 = 0x11eb;
+    const PQ_FIRST: bool = false;
+}
+
+pub type SecP256r1MlKem768 = TlsHybrid<MlKem768, P256, SecP256r1MlKem768Constants>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hybrid::HybridKemConstants;
+    use crate::kdf::{Kdf, Sha3_256};
+    use crate::kem::test::test_all;
+    use crate::{MlKem768P256, MlKem768P256Constants, MlKem768X25519};
+    use hex_literal::hex;
+
+    // Arbitrary ML-KEM-768 inputs.  The tests below check the ECDH half of each exchange against
+    // published values, and the ML-KEM half against ml-kem directly.
+    const MLKEM_SEED: [u8; 64] = [0x11; 64];
+    const MLKEM_RANDOMNESS: [u8; 32] = [0x22; 32];
+
+    #[test]
+    fn x25519_mlkem768() {
+        test_all::<X25519MlKem768>();
+    }
+
+    #[test]
+    fn secp256r1_mlkem768() {
+        test_all::<SecP256r1MlKem768>();
+    }
+
+    // The ECDH half of the exchange uses the key pairs from RFC 7748, Section 6.1
+    #[test]
+    fn x25519_mlkem768_rfc7748() {
+        let alice_sk = hex!("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let alice_pk = hex!("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a");
+        let bob_sk = hex!("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        let bob_pk = hex!("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f");
+        let shared = hex!("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+
+        let seed = [MLKEM_SEED.as_slice(), &alice_sk].concat();
+        let randomness = [MLKEM_RANDOMNESS.as_slice(), &bob_sk].concat();
+
+        let (dk, ek, _) = X25519MlKem768::derive_key_pair(&seed);
+        let (ct, ss) = X25519MlKem768::encaps_derand(&ek, &randomness);
+
+        let (_, ek_pq, _) = MlKem768::derive_key_pair(&MLKEM_SEED);
+        let (ct_pq, ss_pq) = MlKem768::encaps_derand(&ek_pq, &MLKEM_RANDOMNESS);
+
+        assert_eq!(ek, [ek_pq.as_slice(), &alice_pk].concat());
+        assert_eq!(ct, [ct_pq.as_slice(), &bob_pk].concat());
+        assert_eq!(ss, [ss_pq.as_slice(), &shared].concat());
+        assert_eq!(X25519MlKem768::decaps(&dk, &ct), ss);
+    }
+
+    // The ECDH half of the exchange uses the values from RFC 5903, Section 8.1
+    #[test]
+    fn secp256r1_mlkem768_rfc5903() {
+        let i = hex!("c88f01f510d9ac3f70a292daa2316de544e9aab8afe84049c62a9c57862d1433");
+        let gi = hex!(
+            "04"
+            "dad0b65394221cf9b051e1feca5787d098dfe637fc90b9ef945d0c3772581180"
+            "5271a0461cdb8252d61f1c456fa3e59ab1f45b33accf5f58389e0577b8990bb3"
+        );
+        let r = hex!("c6ef9c5d78ae012a011164acb397ce2088685d8f06bf9be0b283ab46476bee53");
+        let gr = hex!(
+            "04"
+            "d12dfb5289c8d4f81208b70270398c342296970a0bccb74c736fc7554494bf63"
+            "56fbf3ca366cc23e8157854c13c58d6aac23f046ada30f8353e74f33039872ab"
+        );
+        let gir = hex!("d6840f6b42f6edafd13116e0e12565202fef8e9ece7dce03812464d04b9442de");
+
+        type C = SecP256r1MlKem768Constants;
+        let (dk_pq, ek_pq, _) = MlKem768::derive_key_pair(&MLKEM_SEED);
+        let (ct_pq, ss_pq) = MlKem768::encaps_derand(&ek_pq, &MLKEM_RANDOMNESS);
+
+        let ek = client_key_share::<P256, C>(&ek_pq, &i.to_vec());
+        assert_eq!(ek, [gi.as_slice(), &ek_pq].concat());
+
+        let (ct, ss) = server_key_share::<P256, C>(&ct_pq, &ss_pq, &gi.to_vec(), &r.to_vec());
+        assert_eq!(ct, [gr.as_slice(), &ct_pq].concat());
+        assert_eq!(ss, [gir.as_slice(), &ss_pq].concat());
+
+        let ss_client = client_shared_secret::<MlKem768, P256, C>(&dk_pq, &i.to_vec(), &ct);
+        assert_eq!(ss_client, ss);
+    }
+
+    // Starting from the same component keys and encapsulation randomness, the TLS group and
+    // MLKEM768-P256 put the same bytes on the wire in a different order, and derive different
+    // shared secrets.
+    #[test]
+    fn secp256r1_mlkem768_vs_draft() {
+        let seed = [7u8; 32];
+        let randomness = [9u8; 64];
+        let (_, ek_h, info) = MlKem768P256::derive_key_pair(&seed);
+        let (ct_h, ss_h) = MlKem768P256::encaps_derand(&ek_h, &randomness);
+
+        type C = SecP256r1MlKem768Constants;
+        let (dk_pq, ek_pq, _) = MlKem768::derive_key_pair(&info.dk_pq);
+        let (ct_pq, ss_pq) = MlKem768::encaps_derand(&ek_pq, &randomness[..32]);
+        let sk_e = P256::random_scalar(&randomness[32..].to_vec());
+
+        let ek = client_key_share::<P256, C>(&ek_pq, &info.dk_t);
+        let (ek_pq_h, ek_t_h) = ek_h.split_at(MlKem768::ENCAPSULATION_KEY_SIZE);
+        assert_eq!(ek, [ek_t_h, ek_pq_h].concat());
+
+        let ek_t = ek_t_h.to_vec();
+        let (ct, ss) = server_key_share::<P256, C>(&ct_pq, &ss_pq, &ek_t, &sk_e);
+        let (ct_pq_h, ct_t_h) = ct_h.split_at(MlKem768::CIPHERTEXT_SIZE);
+        assert_eq!(ct, [ct_t_h, ct_pq_h].concat());
+
+        // TLS concatenates the component secrets; the draft hashes them with the C2PRI combiner
+        let (ss_t, ss_pq) = ss.split_at(P256::SHARED_SECRET_SIZE);
+        let combined = Sha3_256::compute(&[
+            ss_pq,
+            ss_t,
+            ct_t_h,
+            ek_t_h,
+            MlKem768P256Constants::LABEL,
+        ]);
+        assert_eq!(ss.len(), 64);
+        assert_eq!(ss_h, combined);
+        assert_ne!(ss_h, ss[..32]);
+        assert_eq!(
+            client_shared_secret::<MlKem768, P256, C>(&dk_pq, &info.dk_t, &ct),
+            ss
+        );
+    }
+
+    // X25519MLKEM768 uses the same key share layout as MLKEM768-X25519, but the shared secret is
+    // the concatenation of the component secrets rather than the X-Wing combiner output.
+    #[test]
+    fn x25519_mlkem768_vs_draft() {
+        let (_, ek_h, info) = MlKem768X25519::derive_key_pair(&[7u8; 32]);
+        let randomness = [9u8; 64];
+        let (ct_h, ss_h) = MlKem768X25519::encaps_derand(&ek_h, &randomness);
+
+        let (_, ek_pq, _) = MlKem768::derive_key_pair(&info.dk_pq);
+        let ek = client_key_share::<X25519, X25519MlKem768Constants>(&ek_pq, &info.dk_t);
+        assert_eq!(ek, ek_h);
+
+        let (ct, ss) = X25519MlKem768::encaps_derand(&ek, &randomness);
+        assert_eq!(ct, ct_h);
+        assert_eq!(ss.len(), 64);
+        assert_ne!(ss[..32], ss_h);
+        assert_ne!(ss[32..], ss_h);
+    }
+}
@@ -0,0 +1,23 @@
+//! The `stream` binary, run as a subprocess
+
+use concrete_hybrid_kem::MlKem768P256;
+use concrete_hybrid_kem::kem::Kem;
+use std::process::{Command, Stdio};
+
+#[test]
+fn off_curve_recipient() {
+    // The right length for MLKEM768-P256, but the traditional component is not a point
+    let ek = "00".repeat(MlKem768P256::ENCAPSULATION_KEY_SIZE);
+    let output = Command::new(env!("CARGO_BIN_EXE_stream"))
+        .args(["encrypt", &format!("MLKEM768-P256:{}", ek)])
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "Error: invalid recipient or identity\n"
+    );
+}
@@ -0,0 +1,149 @@
+//! WebAssembly bindings for the concrete hybrid KEMs
+//!
+//! A `HybridKem` is constructed from the name of an instance, e.g., `new
+//! HybridKem("MLKEM768-X25519")`, and works on `Uint8Array`s.  Failures throw a JavaScript
+//! `Error` whose `name` is `"HybridKemError"`, both for inputs of the wrong length and for inputs
+//! that cannot be processed (e.g., an invalid curve point).
+//! Randomness for `encaps` comes from `crypto.getRandomValues`, through `getrandom`'s JS backend.
+
+use concrete_hybrid_kem::algorithm::{self, Algorithm};
+use wasm_bindgen::prelude::*;
+
+/// Build the error thrown to JavaScript
+fn error(err: algorithm::Error) -> JsValue {
+    let error = js_sys::Error::new(&err.to_string());
+    error.set_name("HybridKemError");
+    error.into()
+}
+
+/// A hybrid KEM instance
+#[wasm_bindgen]
+pub struct HybridKem {
+    algorithm: Algorithm,
+}
+
+/// A decapsulation key and the corresponding encapsulation key
+#[wasm_bindgen]
+pub struct KeyPair {
+    dk: Vec<u8>,
+    ek: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl KeyPair {
+    #[wasm_bindgen(getter, js_name = decapsulationKey)]
+    pub fn decapsulation_key(&self) -> Vec<u8> {
+        self.dk.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = encapsulationKey)]
+    pub fn encapsulation_key(&self) -> Vec<u8> {
+        self.ek.clone()
+    }
+}
+
+/// A ciphertext and the shared secret it encapsulates
+#[wasm_bindgen]
+pub struct Encapsulation {
+    ct: Vec<u8>,
+    ss: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl Encapsulation {
+    #[wasm_bindgen(getter)]
+    pub fn ciphertext(&self) -> Vec<u8> {
+        self.ct.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = sharedSecret)]
+    pub fn shared_secret(&self) -> Vec<u8> {
+        self.ss.clone()
+    }
+}
+
+#[wasm_bindgen]
+impl HybridKem {
+    /// Select an instance by its draft name or composition string
+    #[wasm_bindgen(constructor)]
+    pub fn new(name: &str) -> Result<HybridKem, JsValue> {
+        let algorithm = name.parse::<Algorithm>().map_err(error)?;
+        Ok(HybridKem { algorithm })
+    }
+
+    /// The names of the instances
+    pub fn algorithms() -> Vec<String> {
+        Algorithm::ALL
+            .iter()
+            .map(|a| a.name().to_string())
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.algorithm.name().to_string()
+    }
+
+    #[wasm_bindgen(getter, js_name = seedSize)]
+    pub fn seed_size(&self) -> usize {
+        self.algorithm.kem().seed_size()
+    }
+
+    #[wasm_bindgen(getter, js_name = encapsulationKeySize)]
+    pub fn encapsulation_key_size(&self) -> usize {
+        self.algorithm.kem().encapsulation_key_size()
+    }
+
+    #[wasm_bindgen(getter, js_name = decapsulationKeySize)]
+    pub fn decapsulation_key_size(&self) -> usize {
+        self.algorithm.kem().decapsulation_key_size()
+    }
+
+    #[wasm_bindgen(getter, js_name = ciphertextSize)]
+    pub fn ciphertext_size(&self) -> usize {
+        self.algorithm.kem().ciphertext_size()
+    }
+
+    #[wasm_bindgen(getter, js_name = sharedSecretSize)]
+    pub fn shared_secret_size(&self) -> usize {
+        self.algorithm.kem().shared_secret_size()
+    }
+
+    #[wasm_bindgen(getter, js_name = randomnessSize)]
+    pub fn randomness_size(&self) -> usize {
+        self.algorithm.kem().randomness_size()
+    }
+
+    /// Derive a key pair from a seed
+    #[wasm_bindgen(js_name = deriveKeyPair)]
+    pub fn derive_key_pair(&self, seed: &[u8]) -> Result<KeyPair, JsValue> {
+        let (dk, ek) = self.algorithm.kem().derive_key_pair(seed).map_err(error)?;
+        Ok(KeyPair { dk, ek })
+    }
+
+    /// Encapsulate to an encapsulation key with randomness from `crypto.getRandomValues`
+    pub fn encaps(&self, ek: &[u8]) -> Result<Encapsulation, JsValue> {
+        let (ss, ct) = self
+            .algorithm
+            .kem()
+            .encaps(ek, &mut rand::rng())
+            .map_err(error)?;
+        Ok(Encapsulation { ct, ss })
+    }
+
+    /// Encapsulate to an encapsulation key with the given randomness
+    #[wasm_bindgen(js_name = encapsDerand)]
+    pub fn encaps_derand(&self, ek: &[u8], randomness: &[u8]) -> Result<Encapsulation, JsValue> {
+        let (ct, ss) = self
+            .algorithm
+            .kem()
+            .encaps_derand(ek, randomness)
+            .map_err(error)?;
+        Ok(Encapsulation { ct, ss })
+    }
+
+    /// Decapsulate a ciphertext, returning the shared secret
+    pub fn decaps(&self, dk: &[u8], ct: &[u8]) -> Result<Vec<u8>, JsValue> {
+        self.algorithm.kem().decaps(dk, ct).map_err(error)
+    }
+}
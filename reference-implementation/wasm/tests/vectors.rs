@@ -0,0 +1,88 @@
+//! Run with `cargo test --target wasm32-unknown-unknown` (or `wasm-pack test --node`) from this
+//! directory, which needs `wasm-bindgen-test-runner` from wasm-bindgen-cli and Node.js.
+
+#![cfg(target_arch = "wasm32")]
+
+use concrete_hybrid_kem::algorithm::Algorithm;
+use concrete_hybrid_kem::test_vectors::TestVectors;
+use concrete_hybrid_kem_wasm::HybridKem;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const TEST_VECTORS: &str = include_str!("../../../test-vectors.json");
+
+#[wasm_bindgen_test]
+fn checked_in_vectors() {
+    let test_vectors: TestVectors = serde_json::from_str(TEST_VECTORS).unwrap();
+
+    for algorithm in Algorithm::ALL {
+        let kem = HybridKem::new(algorithm.name()).unwrap();
+        let vectors = test_vectors.get(algorithm);
+        assert!(!vectors.is_empty());
+
+        for v in vectors {
+            let key_pair = kem.derive_key_pair(&v.seed).unwrap();
+            assert_eq!(key_pair.decapsulation_key(), v.decapsulation_key);
+            assert_eq!(key_pair.encapsulation_key(), v.encapsulation_key);
+
+            let encapsulation = kem
+                .encaps_derand(&v.encapsulation_key, &v.randomness)
+                .unwrap();
+            assert_eq!(encapsulation.ciphertext(), v.ciphertext);
+            assert_eq!(encapsulation.shared_secret(), v.shared_secret);
+
+            let ss = kem.decaps(&v.decapsulation_key, &v.ciphertext).unwrap();
+            assert_eq!(ss, v.shared_secret);
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn round_trip() {
+    for name in HybridKem::algorithms() {
+        let kem = HybridKem::new(&name).unwrap();
+        assert_eq!(kem.name(), name);
+
+        let key_pair = kem.derive_key_pair(&vec![7; kem.seed_size()]).unwrap();
+        let encapsulation = kem.encaps(&key_pair.encapsulation_key()).unwrap();
+        assert_eq!(encapsulation.ciphertext().len(), kem.ciphertext_size());
+
+        let ss = kem
+            .decaps(&key_pair.decapsulation_key(), &encapsulation.ciphertext())
+            .unwrap();
+        assert_eq!(ss, encapsulation.shared_secret());
+    }
+}
+
+#[wasm_bindgen_test]
+fn errors() {
+    let Err(err) = HybridKem::new("MLKEM512-X25519") else {
+        panic!("unknown algorithm accepted");
+    };
+    let err = err.dyn_into::<js_sys::Error>().unwrap();
+    assert_eq!(err.name(), "HybridKemError");
+
+    let kem = HybridKem::new("MLKEM768-X25519").unwrap();
+    let Err(err) = kem.derive_key_pair(&[0; 31]) else {
+        panic!("short seed accepted");
+    };
+    let err = err.dyn_into::<js_sys::Error>().unwrap();
+    assert_eq!(err.name(), "HybridKemError");
+    assert_eq!(
+        String::from(err.message()),
+        "seed: expected 32 bytes, got 31"
+    );
+
+    // An invalid point in the traditional ciphertext
+    let kem = HybridKem::new("MLKEM768-P256").unwrap();
+    let key_pair = kem.derive_key_pair(&[0; 32]).unwrap();
+    let Err(err) = kem.decaps(
+        &key_pair.decapsulation_key(),
+        &vec![0xff; kem.ciphertext_size()],
+    ) else {
+        panic!("invalid point accepted");
+    };
+    let err = err.dyn_into::<js_sys::Error>().unwrap();
+    assert_eq!(err.name(), "HybridKemError");
+    assert_eq!(String::from(err.message()), "ciphertext: invalid value");
+}
@@ -1,6 +1,10 @@
 #!/bin/bash
 
-# Script to regenerate JSON and markdown test vector files
+# Script to regenerate JSON and markdown test vector files, and the sections of the
+# draft that are generated from the code
 cd reference-implementation
 cargo run --bin generate_vectors -- >../test-vectors.json
 cargo run --bin vectors_to_markdown -- ../test-vectors.json >../test-vectors.md
+cargo run --bin constants_to_markdown -- instances >../instances.md
+cargo run --bin constants_to_markdown -- iana >../iana-table.md
+cargo run --bin generate_vectors -- --seeding shake128 --count 3 --format spec --output-dir ../spec